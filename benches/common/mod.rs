@@ -0,0 +1,74 @@
+use std::fmt::Display;
+
+use criterion::{measurement::WallTime, BenchmarkGroup, BenchmarkId};
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, SingleVerifier},
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+/// Smallest `k` such that `2^k` rows fit `rows` usable rows plus the blinding rows
+/// reserved at the end of every column.
+pub fn k_for_rows(rows: usize) -> u32 {
+    let mut k = 4;
+    while (1 << k) < rows + 8 {
+        k += 1;
+    }
+    k
+}
+
+/// Benchmarks keygen, proof creation and verification of `circuit` under the
+/// `keygen`, `prove` and `verify` ids of `group`, all parameterised by `parameter`.
+///
+/// The proof is checked to verify once before anything is measured, so a broken
+/// circuit fails loudly instead of producing meaningless numbers.
+pub fn bench_keygen_prove_verify<C: Circuit<Fp>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    parameter: impl Display,
+    k: u32,
+    circuit: &C,
+    instance: &[Vec<Fp>],
+) {
+    let params: Params<EqAffine> = Params::new(k);
+    let instance: Vec<&[Fp]> = instance.iter().map(|column| &column[..]).collect();
+    let instances = &[&instance[..]];
+
+    group.bench_with_input(BenchmarkId::new("keygen", &parameter), circuit, |b, circuit| {
+        b.iter(|| {
+            let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+            keygen_pk(&params, vk, circuit).expect("keygen_pk should not fail")
+        })
+    });
+
+    let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+    let pk = keygen_pk(&params, vk, circuit).expect("keygen_pk should not fail");
+
+    let prove = || {
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(circuit),
+            instances,
+            OsRng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        transcript.finalize()
+    };
+
+    let proof = prove();
+    let verify = |proof: &[u8]| {
+        let strategy = SingleVerifier::new(&params);
+        let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(proof);
+        verify_proof(&params, pk.get_vk(), strategy, instances, &mut transcript)
+    };
+    assert!(verify(&proof).is_ok(), "proof for {} does not verify", parameter);
+
+    group.bench_function(BenchmarkId::new("prove", &parameter), |b| b.iter(prove));
+    group.bench_with_input(BenchmarkId::new("verify", &parameter), &proof, |b, proof| {
+        b.iter(|| verify(proof))
+    });
+}
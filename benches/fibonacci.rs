@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_examples::fibonacci::{example1, example2, example3, example4};
+use halo2_proofs::pasta::Fp;
+
+mod common;
+use common::{bench_keygen_prove_verify, k_for_rows};
+
+const NROWS: [usize; 3] = [10, 100, 1000];
+
+/// Returns the public inputs `[f(0), f(1), f(nrows - 1)]` for a sequence starting at 1, 1.
+fn public_input(nrows: usize) -> Vec<Fp> {
+    let (mut a, mut b) = (Fp::one(), Fp::one());
+    for _ in 2..nrows {
+        let c = a + b;
+        a = b;
+        b = c;
+    }
+    vec![Fp::one(), Fp::one(), b]
+}
+
+fn bench_example1(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci/example1");
+    group.sample_size(10);
+    for nrows in NROWS {
+        let circuit = example1::MyCircuit::<Fp>::new(nrows);
+        bench_keygen_prove_verify(
+            &mut group,
+            nrows,
+            k_for_rows(nrows),
+            &circuit,
+            &[public_input(nrows)],
+        );
+    }
+    group.finish();
+}
+
+fn bench_example2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci/example2");
+    group.sample_size(10);
+    for nrows in NROWS {
+        let circuit = example2::MyCircuit::<Fp>::new(nrows);
+        bench_keygen_prove_verify(
+            &mut group,
+            nrows,
+            k_for_rows(nrows),
+            &circuit,
+            &[public_input(nrows)],
+        );
+    }
+    group.finish();
+}
+
+fn bench_example3(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci/example3");
+    group.sample_size(10);
+    for (name, b) in [("a == b", 10), ("a != b", 12)] {
+        let circuit = example3::FunctionCircuit {
+            a: Fp::from(10),
+            b: Fp::from(b),
+            c: Fp::from(15),
+        };
+        bench_keygen_prove_verify(&mut group, name, 4, &circuit, &[]);
+    }
+    group.finish();
+}
+
+fn bench_example4(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fibonacci/example4");
+    group.sample_size(10);
+    // The 5-bit xor table takes 1024 rows, whatever the number of steps.
+    let circuit = example4::MyCircuit::<Fp>::default();
+    let public_input = vec![Fp::one(), Fp::one(), Fp::from(21)];
+    bench_keygen_prove_verify(&mut group, 8, 11, &circuit, &[public_input]);
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_example1,
+    bench_example2,
+    bench_example3,
+    bench_example4
+);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_examples::mip::circuits::merkle_v2::MerkleTreeV2Circuit;
+use halo2_proofs::{circuit::Value, pasta::Fp};
+
+mod common;
+use common::{bench_keygen_prove_verify, k_for_rows};
+
+const DEPTHS: [usize; 4] = [4, 8, 16, 32];
+
+fn bench_merkle_v2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle/v2");
+    group.sample_size(10);
+    for depth in DEPTHS {
+        let leaf = 1u64;
+        let elements: Vec<u64> = (0..depth as u64).collect();
+        let indices: Vec<u64> = (0..depth as u64).map(|i| i % 2).collect();
+
+        // `Hash2Chip::hash2` is the mock hash `a + b`, so the root is the plain sum.
+        let root = leaf + elements.iter().sum::<u64>();
+
+        let circuit = MerkleTreeV2Circuit {
+            leaf: Value::known(Fp::from(leaf)),
+            elements: elements.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+            indices: indices.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+        };
+
+        // One private leaf row, then a swap region (2 rows) and a hash region per layer.
+        let rows = 1 + 3 * depth;
+        bench_keygen_prove_verify(
+            &mut group,
+            depth,
+            k_for_rows(rows),
+            &circuit,
+            &[vec![Fp::from(leaf), Fp::from(root)]],
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_merkle_v2);
+criterion_main!(benches);
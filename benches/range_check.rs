@@ -0,0 +1,91 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_examples::range_check::example2::RangeCheckConfig;
+use halo2_proofs::{
+    circuit::{floor_planner::V1, Layouter, Value},
+    pasta::Fp,
+    plonk::{Assigned, Circuit, ConstraintSystem, Error},
+};
+
+mod common;
+use common::{bench_keygen_prove_verify, k_for_rows};
+
+/// Range-checks a single value with the degree-`RANGE` polynomial expression.
+///
+/// The lookup argument is still configured, so it is given a one-row table
+/// holding only the `0` that disabled rows look up.
+#[derive(Default)]
+struct PolynomialCircuit<const RANGE: usize> {
+    value: Value<Assigned<Fp>>,
+}
+
+impl<const RANGE: usize> Circuit<Fp> for PolynomialCircuit<RANGE> {
+    type Config = RangeCheckConfig<Fp, RANGE, 1>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+        config.assign_simple(layouter.namespace(|| "Assign simple value"), self.value)?;
+        Ok(())
+    }
+}
+
+/// Range-checks a single value with a lookup into a `RANGE`-row table.
+#[derive(Default)]
+struct LookupCircuit<const RANGE: usize> {
+    value: Value<Assigned<Fp>>,
+}
+
+impl<const RANGE: usize> Circuit<Fp> for LookupCircuit<RANGE> {
+    type Config = RangeCheckConfig<Fp, 1, RANGE>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+        config.assign_lookup(layouter.namespace(|| "Assign lookup value"), self.value)?;
+        Ok(())
+    }
+}
+
+fn bench_range<const RANGE: usize>(c: &mut Criterion) {
+    // Both strategies check the same in-range witness.
+    let value = Value::known(Fp::from(RANGE as u64 - 1).into());
+
+    let mut group = c.benchmark_group("range_check/polynomial");
+    group.sample_size(10);
+    let circuit = PolynomialCircuit::<RANGE> { value };
+    bench_keygen_prove_verify(&mut group, RANGE, k_for_rows(1), &circuit, &[]);
+    group.finish();
+
+    let mut group = c.benchmark_group("range_check/lookup");
+    group.sample_size(10);
+    let circuit = LookupCircuit::<RANGE> { value };
+    bench_keygen_prove_verify(&mut group, RANGE, k_for_rows(RANGE), &circuit, &[]);
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_range::<4>,
+    bench_range::<8>,
+    bench_range::<16>,
+    bench_range::<32>
+);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+//! Tools for finding soundness holes in chips and measuring their layout.
+
+pub mod layout;
+pub mod mutation;
+mod recorder;
+mod structure;
+pub mod under_constrained;
+
+pub use structure::ColumnRef;
@@ -0,0 +1,46 @@
+//! Measures how much of the grid a circuit uses.
+//!
+//! Floor planners pack regions into the columns they use, so the rows a circuit needs
+//! depend on how its chips split their cells into regions. [`measure`] synthesizes a
+//! circuit the way `MockProver` does, without evaluating any witness, and counts the
+//! regions and the advice rows it takes up.
+
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Circuit, Error},
+};
+
+use super::recorder::record;
+
+/// The findings of [`measure`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// Number of regions, including tables.
+    pub regions: usize,
+    /// Number of rows up to the last assigned advice cell.
+    pub rows: usize,
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} regions, {} rows", self.regions, self.rows)
+    }
+}
+
+/// Synthesizes `circuit` and measures its layout.
+pub fn measure<F: FieldExt, C: Circuit<F>>(circuit: &C) -> Result<Layout, Error> {
+    let (_, recorder) = record(circuit)?;
+    let rows = recorder
+        .advice
+        .keys()
+        .map(|(_, row)| row + 1)
+        .max()
+        .unwrap_or(0);
+
+    Ok(Layout {
+        regions: recorder.regions,
+        rows,
+    })
+}
@@ -0,0 +1,384 @@
+//! Checks that the constraints of a circuit pin down its witness.
+//!
+//! Starting from a satisfying assignment, [`fuzz`] shifts the value of one assigned
+//! advice cell at a time, or of two cells at once, and re-runs `MockProver`. A
+//! mutation that still verifies means the circuit accepts another witness for the
+//! same public inputs, so the mutated cells are "free".
+//!
+//! Cells linked by copy constraints are mutated together, since a malicious prover
+//! would change all of them at once. Cells copied to or from instance or fixed
+//! columns are pinned and never mutated.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+};
+
+use halo2_proofs::{
+    arithmetic::{Field, FieldExt},
+    circuit::{Layouter, Value},
+    dev::MockProver,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+use super::{
+    recorder::{record, CellRef},
+    structure::ColumnRef,
+    under_constrained::CellReport,
+};
+
+/// Which combinations of cells [`fuzz`] mutates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mutations {
+    /// Every cell on its own.
+    Single,
+    /// Every cell on its own, then every pair of cells that are not free on their own.
+    Pairs,
+}
+
+/// The findings of [`fuzz`]. Each cell stands for the cells copy-constrained to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    /// Cells whose value can change on its own.
+    pub free_cells: Vec<CellReport>,
+    /// Pairs of cells that cannot change on their own, but can change together.
+    pub free_pairs: Vec<(CellReport, CellReport)>,
+}
+
+impl Report {
+    /// Returns `true` if no mutation verified.
+    pub fn is_empty(&self) -> bool {
+        self.free_cells.is_empty() && self.free_pairs.is_empty()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no free cells found");
+        }
+        if !self.free_cells.is_empty() {
+            writeln!(f, "free cells:")?;
+            for cell in &self.free_cells {
+                writeln!(f, "  {}", cell)?;
+            }
+        }
+        if !self.free_pairs.is_empty() {
+            writeln!(f, "free pairs of cells:")?;
+            for (a, b) in &self.free_pairs {
+                writeln!(f, "  {}\n    and {}", a, b)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The advice cells to shift, by `(column, row)`, and whether to shift them down
+/// rather than up.
+#[derive(Clone, Debug, Default)]
+struct Mutation {
+    cells: HashSet<(usize, usize)>,
+    negate: bool,
+}
+
+thread_local! {
+    /// The mutation [`Mutating`] applies to the synthesis in progress.
+    ///
+    /// Floor planners are only handed the circuit as a generic `C: Circuit<F>`, so
+    /// this is the one way to pass the mutation down to them.
+    static MUTATION: RefCell<Mutation> = RefCell::new(Mutation::default());
+}
+
+/// A floor planner that lays a circuit out with `P`, applying [`MUTATION`] to the
+/// advice cells it assigns.
+struct Mutating<P>(PhantomData<P>);
+
+impl<P: FloorPlanner> FloorPlanner for Mutating<P> {
+    fn synthesize<F: Field, CS: Assignment<F>, C: Circuit<F>>(
+        cs: &mut CS,
+        circuit: &C,
+        config: C::Config,
+        constants: Vec<Column<Fixed>>,
+    ) -> Result<(), Error> {
+        let mutation = MUTATION.with(|mutation| mutation.borrow().clone());
+        P::synthesize(
+            &mut MutatingAssignment { cs, mutation },
+            circuit,
+            config,
+            constants,
+        )
+    }
+}
+
+struct MutatingAssignment<'a, CS> {
+    cs: &'a mut CS,
+    mutation: Mutation,
+}
+
+impl<'a, F: Field, CS: Assignment<F>> Assignment<F> for MutatingAssignment<'a, CS> {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.enter_region(name_fn)
+    }
+
+    fn exit_region(&mut self) {
+        self.cs.exit_region()
+    }
+
+    fn enable_selector<A, AR>(
+        &mut self,
+        annotation: A,
+        selector: &Selector,
+        row: usize,
+    ) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.enable_selector(annotation, selector, row)
+    }
+
+    fn query_instance(&self, column: Column<Instance>, row: usize) -> Result<Value<F>, Error> {
+        self.cs.query_instance(column, row)
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let delta = if !self.mutation.cells.contains(&(column.index(), row)) {
+            F::zero()
+        } else if self.mutation.negate {
+            -F::one()
+        } else {
+            F::one()
+        };
+        self.cs.assign_advice(annotation, column, row, || {
+            to().into_field().map(|value| value + delta)
+        })
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Fixed>,
+        row: usize,
+        to: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.cs.assign_fixed(annotation, column, row, to)
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.cs.copy(left_column, left_row, right_column, right_row)
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        row: usize,
+        to: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.cs.fill_from_row(column, row, to)
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.cs.push_namespace(name_fn)
+    }
+
+    fn pop_namespace(&mut self, gadget_name: Option<String>) {
+        self.cs.pop_namespace(gadget_name)
+    }
+}
+
+/// Runs the wrapped circuit through the [`Mutating`] floor planner.
+struct Mutated<'c, C>(&'c C);
+
+impl<'c, F: Field, C: Circuit<F>> Circuit<F> for Mutated<'c, C> {
+    type Config = C::Config;
+    type FloorPlanner = Mutating<C::FloorPlanner>;
+
+    fn without_witnesses(&self) -> Self {
+        // Floor planners only use this to measure regions, which does not depend on
+        // the witness.
+        Mutated(self.0)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        C::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        self.0.synthesize(config, layouter)
+    }
+}
+
+/// Returns `true` if `circuit` still verifies with `cells` shifted by one in either
+/// direction.
+fn verifies_with<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instance: &[Vec<F>],
+    cells: &HashSet<(usize, usize)>,
+) -> Result<bool, Error> {
+    for negate in [false, true] {
+        MUTATION.with(|mutation| {
+            *mutation.borrow_mut() = Mutation {
+                cells: cells.clone(),
+                negate,
+            }
+        });
+        let prover = MockProver::run(k, &Mutated(circuit), instance.to_vec());
+        MUTATION.with(|mutation| *mutation.borrow_mut() = Mutation::default());
+
+        if prover?.verify().is_ok() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Returns the representative of the copy group `cell` belongs to.
+fn find(parent: &mut HashMap<CellRef, CellRef>, cell: CellRef) -> CellRef {
+    let next = *parent.entry(cell).or_insert(cell);
+    if next == cell {
+        return cell;
+    }
+    let root = find(parent, next);
+    parent.insert(cell, root);
+    root
+}
+
+/// Groups the assigned advice cells of a recorded circuit by copy constraints,
+/// dropping the groups that reach an instance or fixed cell.
+fn mutable_groups(
+    advice: &HashMap<(usize, usize), (String, String)>,
+    copies: &[(CellRef, CellRef)],
+) -> Vec<Vec<CellRef>> {
+    let mut parent = HashMap::new();
+    let advice_cell = |(index, row): (usize, usize)| {
+        (
+            ColumnRef {
+                column_type: Any::Advice,
+                index,
+            },
+            row,
+        )
+    };
+    for cell in advice.keys() {
+        find(&mut parent, advice_cell(*cell));
+    }
+    for (left, right) in copies {
+        let left = find(&mut parent, *left);
+        let right = find(&mut parent, *right);
+        parent.insert(left, right);
+    }
+
+    let mut groups: HashMap<_, Vec<_>> = HashMap::new();
+    let cells: Vec<_> = parent.keys().copied().collect();
+    for cell in cells {
+        let root = find(&mut parent, cell);
+        groups.entry(root).or_default().push(cell);
+    }
+
+    let mut groups: Vec<_> = groups
+        .into_values()
+        .filter(|group| {
+            group
+                .iter()
+                .all(|(column, _)| column.column_type == Any::Advice)
+        })
+        .map(|mut group| {
+            group.sort_by_key(|(column, row)| (*row, column.index));
+            group
+        })
+        .collect();
+    groups.sort_by_key(|group| (group[0].1, group[0].0.index));
+    groups
+}
+
+/// Mutates the witness of `circuit`, which must satisfy `instance`, and reports the
+/// cells that can change without breaking any constraint.
+pub fn fuzz<F: FieldExt, C: Circuit<F>>(
+    k: u32,
+    circuit: &C,
+    instance: Vec<Vec<F>>,
+    mutations: Mutations,
+) -> Result<Report, Error> {
+    MockProver::run(k, circuit, instance.clone())?.assert_satisfied();
+
+    let (_, recorder) = record(circuit)?;
+    let groups: Vec<_> = mutable_groups(&recorder.advice, &recorder.copies)
+        .into_iter()
+        .map(|group| {
+            let (column, row) = group[0];
+            let (region, annotation) = recorder.advice[&(column.index, row)].clone();
+            let cell = CellReport {
+                region,
+                annotation,
+                column: column.index,
+                row,
+            };
+            let cells: HashSet<_> = group
+                .iter()
+                .map(|(column, row)| (column.index, *row))
+                .collect();
+            (cell, cells)
+        })
+        .collect();
+
+    let mut report = Report::default();
+    let mut pinned = vec![];
+    for (cell, cells) in &groups {
+        if verifies_with(k, circuit, &instance, cells)? {
+            report.free_cells.push(cell.clone());
+        } else {
+            pinned.push((cell, cells));
+        }
+    }
+
+    if mutations == Mutations::Pairs {
+        for (i, (a, a_cells)) in pinned.iter().enumerate() {
+            for (b, b_cells) in &pinned[i + 1..] {
+                let cells = a_cells.union(b_cells).copied().collect();
+                if verifies_with(k, circuit, &instance, &cells)? {
+                    report.free_pairs.push(((*a).clone(), (*b).clone()));
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,175 @@
+//! Records what a circuit assigns during synthesis.
+
+use std::collections::{HashMap, HashSet};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::Value,
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed,
+        FloorPlanner, Instance, Selector,
+    },
+};
+
+use super::structure::{selector_index, CircuitStructure, ColumnRef};
+
+/// A cell by column and absolute row.
+pub(super) type CellRef = (ColumnRef, usize);
+
+/// Records what a circuit assigns during synthesis, without looking at any value.
+#[derive(Default)]
+pub(super) struct Recorder {
+    namespaces: Vec<String>,
+    region: Option<String>,
+    /// Assigned advice cells by `(column, row)`, with their region and annotation.
+    pub advice: HashMap<(usize, usize), (String, String)>,
+    /// Fixed columns that were assigned at least once.
+    pub fixed: HashSet<usize>,
+    /// Rows each selector is enabled on.
+    pub selectors: HashMap<usize, HashSet<usize>>,
+    pub copies: Vec<(CellRef, CellRef)>,
+    /// Number of regions entered, including tables.
+    pub regions: usize,
+}
+
+fn column_ref(column: Column<Any>) -> ColumnRef {
+    ColumnRef {
+        column_type: *column.column_type(),
+        index: column.index(),
+    }
+}
+
+impl<F: FieldExt> Assignment<F> for Recorder {
+    fn enter_region<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let mut path = self.namespaces.clone();
+        path.push(name_fn().into());
+        self.region = Some(path.join("/"));
+        self.regions += 1;
+    }
+
+    fn exit_region(&mut self) {
+        self.region = None;
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, selector: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.selectors
+            .entry(selector_index(selector))
+            .or_default()
+            .insert(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        annotation: A,
+        column: Column<Advice>,
+        row: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let region = self.region.clone().unwrap_or_default();
+        self.advice
+            .insert((column.index(), row), (region, annotation().into()));
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        _: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.fixed.insert(column.index());
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        left_column: Column<Any>,
+        left_row: usize,
+        right_column: Column<Any>,
+        right_row: usize,
+    ) -> Result<(), Error> {
+        self.copies.push((
+            (column_ref(left_column), left_row),
+            (column_ref(right_column), right_row),
+        ));
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        column: Column<Fixed>,
+        _: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.fixed.insert(column.index());
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name_fn: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name_fn().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+}
+
+/// Returns the fixed columns with the given indices.
+///
+/// `Column::new` is private to `halo2_proofs`, but columns compare by type and index,
+/// so allocating columns on a scratch `ConstraintSystem` reproduces them.
+fn fixed_columns<F: FieldExt>(indices: &[usize]) -> Vec<Column<Fixed>> {
+    let mut scratch = ConstraintSystem::<F>::default();
+    let count = indices.iter().max().map_or(0, |max| max + 1);
+    let columns: Vec<_> = (0..count).map(|_| scratch.fixed_column()).collect();
+    indices.iter().map(|index| columns[*index]).collect()
+}
+
+/// Configures `circuit` and synthesizes it into a [`Recorder`]. No witness value is
+/// evaluated, so the circuit may be one returned by `without_witnesses`.
+pub(super) fn record<F: FieldExt, C: Circuit<F>>(
+    circuit: &C,
+) -> Result<(CircuitStructure, Recorder), Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let structure = CircuitStructure::from_constraint_system(&cs);
+
+    let mut recorder = Recorder::default();
+    C::FloorPlanner::synthesize(
+        &mut recorder,
+        circuit,
+        config,
+        fixed_columns::<F>(&structure.constants),
+    )?;
+    Ok((structure, recorder))
+}
@@ -0,0 +1,391 @@
+//! Recovers the shape of a configured circuit from its `ConstraintSystem`.
+//!
+//! `halo2_proofs` keeps the gates and lookups of a `ConstraintSystem` private, but
+//! its `Debug` output is complete. We parse that output into a small tree and read
+//! the pieces the analyses need: which selectors turn each gate or lookup on and which
+//! cells it queries.
+
+use std::{iter::Peekable, str::Chars};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, ConstraintSystem},
+};
+
+/// A column of the circuit, identified by its type and index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ColumnRef {
+    pub column_type: Any,
+    pub index: usize,
+}
+
+/// A cell queried relative to the row a gate or lookup is evaluated at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Query {
+    pub column: ColumnRef,
+    pub rotation: i32,
+}
+
+/// A gate or lookup argument, reduced to what it turns on and what it reads.
+#[derive(Clone, Debug)]
+pub(crate) struct Constraint {
+    pub name: String,
+    /// Selectors multiplied into the constraint. An empty list means the constraint
+    /// applies on every usable row.
+    pub selectors: Vec<usize>,
+    pub queries: Vec<Query>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct CircuitStructure {
+    pub num_advice_columns: usize,
+    pub num_fixed_columns: usize,
+    pub num_instance_columns: usize,
+    pub num_selectors: usize,
+    pub blinding_factors: usize,
+    pub gates: Vec<Constraint>,
+    /// Lookups, with the queries of their input expressions only.
+    pub lookups: Vec<Constraint>,
+    pub constants: Vec<usize>,
+}
+
+impl CircuitStructure {
+    pub fn from_constraint_system<F: FieldExt>(cs: &ConstraintSystem<F>) -> Self {
+        let root = Node::parse(&format!("{:?}", cs));
+
+        let count = |name: &str| root.field(name).and_then(Node::as_usize).unwrap_or(0);
+
+        let gates = root
+            .field("gates")
+            .map(Node::items)
+            .unwrap_or_default()
+            .iter()
+            .map(|gate| {
+                let mut selectors = vec![];
+                let mut queries = vec![];
+                if let Some(polys) = gate.field("polys").or_else(|| gate.field("polynomials")) {
+                    collect_expression(polys, &mut selectors, &mut queries);
+                }
+                Constraint {
+                    name: gate
+                        .field("name")
+                        .map(|name| name.atom().trim_matches('"').to_string())
+                        .unwrap_or_default(),
+                    selectors,
+                    queries,
+                }
+            })
+            .collect();
+
+        let lookups = root
+            .field("lookups")
+            .map(Node::items)
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(index, lookup)| {
+                let mut selectors = vec![];
+                let mut queries = vec![];
+                if let Some(input) = lookup.field("input_expressions") {
+                    collect_expression(input, &mut selectors, &mut queries);
+                }
+                Constraint {
+                    name: format!("lookup {}", index),
+                    selectors,
+                    queries,
+                }
+            })
+            .collect();
+
+        let constants = root
+            .field("constants")
+            .map(Node::items)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Node::as_column)
+            .map(|column| column.index)
+            .collect();
+
+        Self {
+            num_advice_columns: count("num_advice_columns"),
+            num_fixed_columns: count("num_fixed_columns"),
+            num_instance_columns: count("num_instance_columns"),
+            num_selectors: count("num_selectors"),
+            blinding_factors: cs.blinding_factors(),
+            gates,
+            lookups,
+            constants,
+        }
+    }
+}
+
+/// Returns the index of a `Selector`, which is only exposed through its `Debug` output.
+pub(crate) fn selector_index(selector: &impl std::fmt::Debug) -> usize {
+    let node = Node::parse(&format!("{:?}", selector));
+    node.items()
+        .first()
+        .and_then(Node::as_usize)
+        .expect("Selector should print as `Selector(index, simple)`")
+}
+
+/// Walks an expression, collecting the selectors it multiplies in and the cells it
+/// queries.
+///
+/// Queries print either as `Advice { query_index, column_index, rotation }` or as
+/// `Advice(AdviceQuery { index, column_index, rotation })`, depending on the
+/// `halo2_proofs` revision, so both forms are accepted.
+fn collect_expression(node: &Node, selectors: &mut Vec<usize>, queries: &mut Vec<Query>) {
+    match node {
+        Node::Tuple(name, items) if name == "Selector" => match items.as_slice() {
+            [index, _simple] if index.as_usize().is_some() => {
+                let index = index.as_usize().unwrap();
+                if !selectors.contains(&index) {
+                    selectors.push(index);
+                }
+            }
+            _ => items
+                .iter()
+                .for_each(|item| collect_expression(item, selectors, queries)),
+        },
+        Node::Struct(name, fields) => {
+            let column_type = match name.trim_end_matches("Query") {
+                "Advice" => Some(Any::Advice),
+                "Fixed" => Some(Any::Fixed),
+                "Instance" => Some(Any::Instance),
+                _ => None,
+            };
+            let column_index = node.field("column_index").and_then(Node::as_usize);
+            let rotation = node
+                .field("rotation")
+                .and_then(|rotation| rotation.items().first().and_then(Node::as_i32));
+            match (column_type, column_index, rotation) {
+                (Some(column_type), Some(index), Some(rotation)) => {
+                    let query = Query {
+                        column: ColumnRef { column_type, index },
+                        rotation,
+                    };
+                    if !queries.contains(&query) {
+                        queries.push(query);
+                    }
+                }
+                _ => fields
+                    .iter()
+                    .for_each(|(_, field)| collect_expression(field, selectors, queries)),
+            }
+        }
+        Node::Tuple(_, items) | Node::List(items) => items
+            .iter()
+            .for_each(|item| collect_expression(item, selectors, queries)),
+        Node::Atom(_) => {}
+    }
+}
+
+/// A parsed `Debug` representation.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    /// `Name { field: node, .. }`
+    Struct(String, Vec<(String, Node)>),
+    /// `Name(node, ..)`, or `(node, ..)` with an empty name.
+    Tuple(String, Vec<Node>),
+    /// `[node, ..]`
+    List(Vec<Node>),
+    /// Identifiers, numbers and string literals.
+    Atom(String),
+}
+
+impl Node {
+    fn parse(input: &str) -> Self {
+        let mut chars = input.chars().peekable();
+        let node = parse_node(&mut chars);
+        skip_whitespace(&mut chars);
+        assert!(chars.next().is_none(), "trailing input in `Debug` output");
+        node
+    }
+
+    fn field(&self, name: &str) -> Option<&Node> {
+        match self {
+            Node::Struct(_, fields) => fields
+                .iter()
+                .find(|(field, _)| field == name)
+                .map(|(_, node)| node),
+            _ => None,
+        }
+    }
+
+    fn items(&self) -> &[Node] {
+        match self {
+            Node::Tuple(_, items) | Node::List(items) => items,
+            _ => &[],
+        }
+    }
+
+    fn atom(&self) -> &str {
+        match self {
+            Node::Atom(atom) => atom,
+            _ => "",
+        }
+    }
+
+    fn as_usize(&self) -> Option<usize> {
+        self.atom().parse().ok()
+    }
+
+    fn as_i32(&self) -> Option<i32> {
+        self.atom().parse().ok()
+    }
+
+    /// Reads `Column { index, column_type }`.
+    fn as_column(&self) -> Option<ColumnRef> {
+        let index = self.field("index")?.as_usize()?;
+        let column_type = match self.field("column_type")?.atom() {
+            "Advice" => Any::Advice,
+            "Fixed" => Any::Fixed,
+            "Instance" => Any::Instance,
+            _ => return None,
+        };
+        Some(ColumnRef { column_type, index })
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while matches!(chars.peek(), Some(c) if c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) {
+    skip_whitespace(chars);
+    let found = chars.next();
+    assert_eq!(found, Some(expected), "malformed `Debug` output");
+}
+
+/// Parses a comma-separated sequence up to `close`, consuming the delimiter.
+fn parse_sequence<T>(
+    chars: &mut Peekable<Chars>,
+    close: char,
+    mut item: impl FnMut(&mut Peekable<Chars>) -> T,
+) -> Vec<T> {
+    let mut items = vec![];
+    loop {
+        skip_whitespace(chars);
+        if chars.peek() == Some(&close) {
+            chars.next();
+            return items;
+        }
+        items.push(item(chars));
+        skip_whitespace(chars);
+        if chars.peek() == Some(&',') {
+            chars.next();
+        }
+    }
+}
+
+fn parse_node(chars: &mut Peekable<Chars>) -> Node {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('[') => {
+            chars.next();
+            Node::List(parse_sequence(chars, ']', parse_node))
+        }
+        Some('(') => {
+            chars.next();
+            Node::Tuple(String::new(), parse_sequence(chars, ')', parse_node))
+        }
+        Some('"') => {
+            chars.next();
+            let mut literal = String::from('"');
+            while let Some(c) = chars.next() {
+                literal.push(c);
+                match c {
+                    '\\' => literal.extend(chars.next()),
+                    '"' => break,
+                    _ => {}
+                }
+            }
+            Node::Atom(literal)
+        }
+        _ => {
+            let mut token = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || ",:()[]{}".contains(c) {
+                    break;
+                }
+                token.push(c);
+                chars.next();
+            }
+            assert!(!token.is_empty(), "malformed `Debug` output");
+
+            skip_whitespace(chars);
+            match chars.peek() {
+                Some('(') => {
+                    chars.next();
+                    Node::Tuple(token, parse_sequence(chars, ')', parse_node))
+                }
+                Some('{') => {
+                    chars.next();
+                    let fields = parse_sequence(chars, '}', |chars| {
+                        let name = match parse_node(chars) {
+                            Node::Atom(name) => name,
+                            _ => panic!("malformed `Debug` output"),
+                        };
+                        expect(chars, ':');
+                        (name, parse_node(chars))
+                    });
+                    Node::Struct(token, fields)
+                }
+                _ => Node::Atom(token),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CircuitStructure, ColumnRef, Query};
+    use halo2_proofs::{
+        pasta::Fp,
+        plonk::{Any, ConstraintSystem},
+        poly::Rotation,
+    };
+
+    #[test]
+    fn structure_of_gate_and_lookup() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let table = meta.lookup_table_column();
+        let s_gate = meta.selector();
+        let s_lookup = meta.complex_selector();
+
+        meta.create_gate("double", |meta| {
+            let s = meta.query_selector(s_gate);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::next());
+            vec![s * (a.clone() + a - b)]
+        });
+        meta.lookup(|meta| {
+            let s = meta.query_selector(s_lookup);
+            let a = meta.query_advice(a, Rotation::prev());
+            vec![(s * a, table)]
+        });
+
+        let structure = CircuitStructure::from_constraint_system(&meta);
+        let advice = |index, rotation| Query {
+            column: ColumnRef {
+                column_type: Any::Advice,
+                index,
+            },
+            rotation,
+        };
+
+        assert_eq!(structure.num_advice_columns, 2);
+        assert_eq!(structure.num_selectors, 2);
+        assert_eq!(structure.gates.len(), 1);
+        assert_eq!(structure.gates[0].name, "double");
+        assert_eq!(structure.gates[0].selectors, vec![0]);
+        assert_eq!(structure.gates[0].queries, vec![advice(0, 0), advice(1, 1)]);
+        assert_eq!(structure.lookups.len(), 1);
+        assert_eq!(structure.lookups[0].selectors, vec![1]);
+        assert_eq!(structure.lookups[0].queries, vec![advice(0, -1)]);
+    }
+}
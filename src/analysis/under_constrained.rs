@@ -0,0 +1,309 @@
+//! Finds advice cells that are assigned but not constrained.
+//!
+//! A witness cell that no enabled gate or lookup reads can take any value in a proof:
+//! the prover fills it in, but nothing checks it. [`analyze`] synthesizes a circuit
+//! the way `MockProver` does, without evaluating any witness, and reports
+//!
+//! - assigned advice cells that no enabled gate, active lookup or copy constraint
+//!   refers to,
+//! - assigned advice cells whose only constraints are copy constraints, i.e. values
+//!   that are moved around but never computed by a gate,
+//! - columns that are never assigned, queried or copied, and
+//! - selectors that are never enabled, which leaves their gates and lookups inert.
+//!
+//! Copy-only cells are expected where a chip loads a private input, so that list is
+//! something to review rather than a list of bugs.
+
+use std::{collections::HashSet, fmt};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    plonk::{Any, Circuit, Error},
+};
+
+use super::{recorder::record, structure::ColumnRef};
+
+/// An advice cell reported by [`analyze`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CellReport {
+    /// The region the cell was assigned in, prefixed by the enclosing namespaces.
+    pub region: String,
+    pub annotation: String,
+    /// Index of the advice column.
+    pub column: usize,
+    pub row: usize,
+}
+
+/// A selector that is never enabled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelectorReport {
+    pub index: usize,
+    /// The gates and lookups that only apply where this selector is enabled.
+    pub constraints: Vec<String>,
+}
+
+/// The findings of [`analyze`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub unreferenced_cells: Vec<CellReport>,
+    pub copy_only_cells: Vec<CellReport>,
+    pub unused_columns: Vec<ColumnRef>,
+    pub unused_selectors: Vec<SelectorReport>,
+}
+
+impl Report {
+    /// Returns `true` if the report has no findings at all.
+    pub fn is_empty(&self) -> bool {
+        self.unreferenced_cells.is_empty()
+            && self.copy_only_cells.is_empty()
+            && self.unused_columns.is_empty()
+            && self.unused_selectors.is_empty()
+    }
+}
+
+impl fmt::Display for ColumnRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let column_type = match self.column_type {
+            Any::Advice => "advice",
+            Any::Fixed => "fixed",
+            Any::Instance => "instance",
+        };
+        write!(f, "{}[{}]", column_type, self.index)
+    }
+}
+
+impl fmt::Display for CellReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "advice[{}] row {}: `{}` in region `{}`",
+            self.column, self.row, self.annotation, self.region
+        )
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no under-constrained cells found");
+        }
+        if !self.unreferenced_cells.is_empty() {
+            writeln!(f, "assigned cells with no constraint:")?;
+            for cell in &self.unreferenced_cells {
+                writeln!(f, "  {}", cell)?;
+            }
+        }
+        if !self.copy_only_cells.is_empty() {
+            writeln!(f, "assigned cells with only copy constraints:")?;
+            for cell in &self.copy_only_cells {
+                writeln!(f, "  {}", cell)?;
+            }
+        }
+        if !self.unused_columns.is_empty() {
+            writeln!(f, "unused columns:")?;
+            for column in &self.unused_columns {
+                writeln!(f, "  {}", column)?;
+            }
+        }
+        if !self.unused_selectors.is_empty() {
+            writeln!(f, "selectors that are never enabled:")?;
+            for selector in &self.unused_selectors {
+                writeln!(
+                    f,
+                    "  selector {} (used by {})",
+                    selector.index,
+                    selector.constraints.join(", ")
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Synthesizes `circuit` with `2^k` rows and reports the cells and columns it leaves
+/// unconstrained.
+pub fn analyze<F: FieldExt, C: Circuit<F>>(k: u32, circuit: &C) -> Result<Report, Error> {
+    let (structure, recorder) = record(circuit)?;
+    let usable_rows = (1usize << k) - (structure.blinding_factors + 1);
+
+    let mut referenced = HashSet::new();
+    for constraint in structure.gates.iter().chain(structure.lookups.iter()) {
+        // A constraint multiplied by several selectors only applies on the rows where
+        // all of them are enabled.
+        let enabled = |selector| {
+            recorder
+                .selectors
+                .get(selector)
+                .cloned()
+                .unwrap_or_default()
+        };
+        let rows: HashSet<usize> = match constraint.selectors.split_first() {
+            None => (0..usable_rows).collect(),
+            Some((first, rest)) => rest.iter().fold(enabled(first), |rows, selector| {
+                rows.intersection(&enabled(selector)).copied().collect()
+            }),
+        };
+        for row in rows {
+            for query in &constraint.queries {
+                if let Ok(row) = usize::try_from(row as i64 + query.rotation as i64) {
+                    referenced.insert((query.column, row));
+                }
+            }
+        }
+    }
+
+    let copied: HashSet<_> = recorder
+        .copies
+        .iter()
+        .flat_map(|(left, right)| [*left, *right])
+        .collect();
+
+    let mut assigned: Vec<_> = recorder.advice.iter().collect();
+    assigned.sort_by_key(|((column, row), _)| (*row, *column));
+
+    let mut report = Report::default();
+    for ((column, row), (region, annotation)) in assigned {
+        let cell = (
+            ColumnRef {
+                column_type: Any::Advice,
+                index: *column,
+            },
+            *row,
+        );
+        if referenced.contains(&cell) {
+            continue;
+        }
+        let cell_report = CellReport {
+            region: region.clone(),
+            annotation: annotation.clone(),
+            column: *column,
+            row: *row,
+        };
+        if copied.contains(&cell) {
+            report.copy_only_cells.push(cell_report);
+        } else {
+            report.unreferenced_cells.push(cell_report);
+        }
+    }
+
+    let queried: HashSet<ColumnRef> = structure
+        .gates
+        .iter()
+        .chain(structure.lookups.iter())
+        .flat_map(|constraint| constraint.queries.iter().map(|query| query.column))
+        .collect();
+    let assigned_columns: HashSet<ColumnRef> = recorder
+        .advice
+        .keys()
+        .map(|(index, _)| ColumnRef {
+            column_type: Any::Advice,
+            index: *index,
+        })
+        .chain(recorder.fixed.iter().map(|index| ColumnRef {
+            column_type: Any::Fixed,
+            index: *index,
+        }))
+        .chain(copied.iter().map(|(column, _)| *column))
+        .collect();
+    let columns = [
+        (Any::Advice, structure.num_advice_columns),
+        (Any::Fixed, structure.num_fixed_columns),
+        (Any::Instance, structure.num_instance_columns),
+    ];
+    report.unused_columns = columns
+        .iter()
+        .flat_map(|(column_type, count)| {
+            (0..*count).map(move |index| ColumnRef {
+                column_type: *column_type,
+                index,
+            })
+        })
+        .filter(|column| !queried.contains(column) && !assigned_columns.contains(column))
+        .collect();
+
+    report.unused_selectors = (0..structure.num_selectors)
+        .filter(|index| !recorder.selectors.contains_key(index))
+        .map(|index| SelectorReport {
+            index,
+            constraints: structure
+                .gates
+                .iter()
+                .chain(structure.lookups.iter())
+                .filter(|constraint| constraint.selectors.contains(&index))
+                .map(|constraint| constraint.name.clone())
+                .collect(),
+        })
+        .collect();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        pasta::Fp,
+        plonk::{Advice, Column, ConstraintSystem, Selector},
+        poly::Rotation,
+    };
+
+    /// A gate multiplied by two selectors, with only the first one enabled at row 0.
+    struct TwoSelectorCircuit;
+
+    impl Circuit<Fp> for TwoSelectorCircuit {
+        type Config = (Column<Advice>, Selector, Selector);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            // Simple selectors cannot be multiplied together.
+            let (s1, s2) = (meta.complex_selector(), meta.complex_selector());
+            meta.create_gate("zero", |meta| {
+                let s1 = meta.query_selector(s1);
+                let s2 = meta.query_selector(s2);
+                vec![s1 * s2 * meta.query_advice(a, Rotation::cur())]
+            });
+            (a, s1, s2)
+        }
+
+        fn synthesize(
+            &self,
+            (a, s1, s2): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "zeros",
+                |mut region| {
+                    for row in 0..2 {
+                        s1.enable(&mut region, row)?;
+                        region.assign_advice(|| "a", a, row, || Value::known(Fp::zero()))?;
+                    }
+                    s2.enable(&mut region, 1)
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn gate_needs_all_its_selectors() {
+        let report = analyze(4, &TwoSelectorCircuit).unwrap();
+        assert_eq!(
+            report.unreferenced_cells,
+            vec![CellReport {
+                region: "zeros".to_string(),
+                annotation: "a".to_string(),
+                column: 0,
+                row: 0,
+            }],
+            "{}",
+            report
+        );
+        assert!(report.copy_only_cells.is_empty(), "{}", report);
+        assert!(report.unused_selectors.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,412 @@
+use crate::boolean::AssignedBit;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct BitsConfig {
+    pub advice: [Column<Advice>; 3],
+    pub modulus_bit: Column<Fixed>,
+    q_start: Selector,
+    q_bits: Selector,
+    q_strict: Selector,
+}
+
+/// Decomposes a cell into bits and recomposes bits into a cell with a running sum,
+/// most significant bit first:
+///
+///   bit  |         acc         |  eq  | modulus_bit | q_start | q_bits | q_strict
+/// -------+---------------------+------+-------------+---------+--------+----------
+///        |          0          |  1   |             |    1    |   0    |    0
+///  b_n-1 | 2 * acc_prev + bit  | eq_1 |    p_n-1    |    0    |   1    |  strict
+///   ..   |         ..          |  ..  |     ..      |    0    |   1    |  strict
+///   b_0  |          x          | eq_n |     p_0     |    0    |   1    |  strict
+///
+/// Bits are handed out least significant first. With 255 bits, `x + p` has a
+/// decomposition as well, so the strict mode also walks the bits of `p - 1` in the
+/// fixed column: `eq` stays 1 while the bits so far equal those of `p - 1`, and a
+/// bit may only be set where `p - 1` has a 0 once the prefix is already smaller. The
+/// bits are then at most `p - 1`, which makes them the canonical decomposition.
+#[derive(Debug, Clone)]
+pub struct BitsChip<F: FieldExt> {
+    config: BitsConfig,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> BitsChip<F> {
+    pub fn construct(config: BitsConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        modulus_bit: Column<Fixed>,
+    ) -> BitsConfig {
+        let [bit, acc, eq] = advice;
+        meta.enable_equality(bit);
+        meta.enable_equality(acc);
+
+        let q_start = meta.selector();
+        let q_bits = meta.selector();
+        let q_strict = meta.selector();
+
+        meta.create_gate("running sum start", |meta| {
+            let s = meta.query_selector(q_start);
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let eq = meta.query_advice(eq, Rotation::cur());
+            Constraints::with_selector(
+                s,
+                [
+                    ("acc starts at 0", acc),
+                    ("eq starts at 1", Expression::Constant(F::one()) - eq),
+                ],
+            )
+        });
+
+        meta.create_gate("bits", |meta| {
+            let s = meta.query_selector(q_bits);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let acc_prev = meta.query_advice(acc, Rotation::prev());
+            let acc = meta.query_advice(acc, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+            Constraints::with_selector(
+                s,
+                [
+                    ("bit is boolean", bit.clone() * (one - bit.clone())),
+                    ("acc = 2 * acc_prev + bit", two * acc_prev + bit - acc),
+                ],
+            )
+        });
+
+        meta.create_gate("canonical", |meta| {
+            let s = meta.query_selector(q_strict);
+            let bit = meta.query_advice(bit, Rotation::cur());
+            let eq_prev = meta.query_advice(eq, Rotation::prev());
+            let eq = meta.query_advice(eq, Rotation::cur());
+            let modulus_bit = meta.query_fixed(modulus_bit, Rotation::cur());
+            let one = || Expression::Constant(F::one());
+            let same_bit = modulus_bit.clone() * bit.clone()
+                + (one() - modulus_bit.clone()) * (one() - bit.clone());
+            Constraints::with_selector(
+                s,
+                [
+                    (
+                        "bits are at most p - 1",
+                        eq_prev.clone() * (one() - modulus_bit) * bit,
+                    ),
+                    (
+                        "eq = eq_prev * (bit == modulus bit)",
+                        eq_prev * same_bit - eq,
+                    ),
+                ],
+            )
+        });
+
+        BitsConfig {
+            advice,
+            modulus_bit,
+            q_start,
+            q_bits,
+            q_strict,
+        }
+    }
+
+    /// Returns the `n` bits of `x`, least significant first. Fails the running sum if
+    /// `x` does not fit in `n` bits.
+    pub fn to_bits(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        n: usize,
+    ) -> Result<Vec<AssignedBit<F>>, Error> {
+        let bits = x.value().map(|x| le_bits(x, n)).transpose_vec(n);
+        self.assign_to_bits(layouter, x, bits, false)
+    }
+
+    /// Returns the canonical `F::NUM_BITS` bits of `x`, least significant first.
+    pub fn to_bits_strict(
+        &self,
+        layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+    ) -> Result<Vec<AssignedBit<F>>, Error> {
+        let n = F::NUM_BITS as usize;
+        let bits = x.value().map(|x| le_bits(x, n)).transpose_vec(n);
+        self.assign_to_bits(layouter, x, bits, true)
+    }
+
+    /// Returns the number whose bits are `bits`, least significant first.
+    pub fn from_bits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bits: &[AssignedBit<F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "from_bits",
+            |mut region| {
+                let column = self.config.advice[0];
+                self.running_sum(&mut region, bits.len(), false, |region, i, row| {
+                    bits[i].copy_advice(|| "bit", region, column, row)
+                })?;
+                let value = bits.iter().rev().fold(Value::known(F::zero()), |acc, bit| {
+                    acc.zip(bit.value()).map(|(acc, bit)| acc.double() + bit)
+                });
+                region.assign_advice(|| "x", self.config.advice[1], bits.len(), || value)
+            },
+        )
+    }
+
+    /// Lays out the decomposition of `x` into the given bits, which `to_bits` takes
+    /// from `x`.
+    fn assign_to_bits(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+        bits: Vec<Value<bool>>,
+        strict: bool,
+    ) -> Result<Vec<AssignedBit<F>>, Error> {
+        let n = bits.len();
+        assert!(
+            n <= F::NUM_BITS as usize,
+            "x has at most {} bits",
+            F::NUM_BITS
+        );
+        assert!(!strict || n == F::NUM_BITS as usize);
+
+        layouter.assign_region(
+            || "to_bits",
+            |mut region| {
+                let column = self.config.advice[0];
+                let bits = self.running_sum(&mut region, n, strict, |region, i, row| {
+                    let bit = bits[i].map(|bit| F::from(bit as u64));
+                    region.assign_advice(|| "bit", column, row, || bit)
+                })?;
+                x.copy_advice(|| "x", &mut region, self.config.advice[1], n)?;
+                Ok(bits)
+            },
+        )
+    }
+
+    /// Lays out an `n`-bit running sum, assigning bit `i` with `assign_bit(region, i,
+    /// row)`. The last `acc` is left to the caller, who either copies `x` there or
+    /// assigns the recomposed value.
+    fn running_sum(
+        &self,
+        region: &mut Region<'_, F>,
+        n: usize,
+        strict: bool,
+        assign_bit: impl Fn(&mut Region<'_, F>, usize, usize) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<Vec<AssignedBit<F>>, Error> {
+        let [_, acc_column, eq_column] = self.config.advice;
+        let modulus_bits = le_bits(&-F::one(), F::NUM_BITS as usize);
+
+        self.config.q_start.enable(region, 0)?;
+        let mut acc = region.assign_advice(|| "acc", acc_column, 0, || Value::known(F::zero()))?;
+        let mut eq = region.assign_advice(|| "eq", eq_column, 0, || Value::known(F::one()))?;
+
+        let mut bits = vec![None; n];
+        for row in 1..=n {
+            let i = n - row;
+            self.config.q_bits.enable(region, row)?;
+            let bit = assign_bit(region, i, row)?;
+
+            if strict {
+                self.config.q_strict.enable(region, row)?;
+                let modulus_bit = F::from(modulus_bits[i] as u64);
+                region.assign_fixed(
+                    || "modulus bit",
+                    self.config.modulus_bit,
+                    row,
+                    || Value::known(modulus_bit),
+                )?;
+                let same_bit = bit.value().map(|bit| F::from((*bit == modulus_bit) as u64));
+                let value = eq
+                    .value()
+                    .copied()
+                    .zip(same_bit)
+                    .map(|(eq, same)| eq * same);
+                eq = region.assign_advice(|| "eq", eq_column, row, || value)?;
+            }
+
+            if row < n {
+                let value = acc
+                    .value()
+                    .zip(bit.value())
+                    .map(|(acc, bit)| acc.double() + bit);
+                acc = region.assign_advice(|| "acc", acc_column, row, || value)?;
+            }
+            bits[i] = Some(AssignedBit::new(bit));
+        }
+
+        Ok(bits.into_iter().map(Option::unwrap).collect())
+    }
+}
+
+/// The `n` least significant bits of `x`, least significant first.
+fn le_bits<F: FieldExt>(x: &F, n: usize) -> Vec<bool> {
+    let repr = x.to_repr();
+    let bytes = repr.as_ref();
+    (0..n).map(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Reads `x` from instance row 0, and exposes its bits at the rows after it and
+    /// `from_bits` of them after those.
+    struct MyCircuit {
+        n: usize,
+        strict: bool,
+        /// Witnesses these bits instead of the ones of `x`.
+        forged: Option<Vec<bool>>,
+    }
+
+    impl MyCircuit {
+        fn new(n: usize, strict: bool) -> Self {
+            Self {
+                n,
+                strict,
+                forged: None,
+            }
+        }
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (BitsConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                n: self.n,
+                strict: self.strict,
+                forged: self.forged.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let modulus_bit = meta.fixed_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (BitsChip::configure(meta, advice, modulus_bit), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BitsChip::construct(config.clone());
+            let x = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    region.assign_advice_from_instance(|| "x", instance, 0, config.advice[0], 0)
+                },
+            )?;
+
+            let bits = match &self.forged {
+                None if self.strict => chip.to_bits_strict(layouter.namespace(|| "x"), &x)?,
+                None => chip.to_bits(layouter.namespace(|| "x"), &x, self.n)?,
+                Some(bits) => {
+                    let bits = bits.iter().copied().map(Value::known).collect();
+                    chip.assign_to_bits(layouter.namespace(|| "x"), &x, bits, self.strict)?
+                }
+            };
+            let y = chip.from_bits(layouter.namespace(|| "y"), &bits)?;
+
+            for (row, bit) in bits.iter().enumerate() {
+                layouter.constrain_instance(bit.cell(), instance, row + 1)?;
+            }
+            layouter.constrain_instance(y.cell(), instance, bits.len() + 1)
+        }
+    }
+
+    fn instance(x: Fp, bits: &[bool]) -> Vec<Fp> {
+        let bits = bits.iter().map(|bit| Fp::from(*bit as u64));
+        std::iter::once(x).chain(bits).chain([x]).collect()
+    }
+
+    #[test]
+    fn to_bits_and_from_bits() {
+        let circuit = MyCircuit::new(8, false);
+        for x in [0, 1, 5, 128, 200, 255] {
+            let x = Fp::from(x);
+            let instance = instance(x, &le_bits(&x, 8));
+            let prover = MockProver::run(5, &circuit, vec![instance.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            for row in 1..instance.len() {
+                let mut wrong = instance.clone();
+                wrong[row] = Fp::one() - wrong[row];
+                let prover = MockProver::run(5, &circuit, vec![wrong]).unwrap();
+                assert!(prover.verify().is_err(), "x = {:?}, row {}", x, row);
+            }
+        }
+    }
+
+    #[test]
+    fn to_bits_rejects_wide_values() {
+        let circuit = MyCircuit::new(8, false);
+        for x in [Fp::from(256), Fp::from(1000), -Fp::one()] {
+            let instance = instance(x, &le_bits(&x, 8));
+            let prover = MockProver::run(5, &circuit, vec![instance]).unwrap();
+            assert!(prover.verify().is_err(), "x = {:?}", x);
+        }
+    }
+
+    #[test]
+    fn to_bits_strict() {
+        let circuit = MyCircuit::new(255, true);
+        for x in [
+            Fp::zero(),
+            Fp::from(7),
+            -Fp::one(),
+            -Fp::from(2).pow(&[128, 0, 0, 0]),
+        ] {
+            let instance = instance(x, &le_bits(&x, 255));
+            let prover = MockProver::run(10, &circuit, vec![instance]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+
+    #[test]
+    fn to_bits_strict_rejects_non_canonical_bits() {
+        for x in [0, 5] {
+            // The bits of x + p, which recompose to x in the field.
+            let mut bits = le_bits(&-Fp::one(), 255);
+            for _ in 0..=x {
+                for bit in bits.iter_mut() {
+                    *bit = !*bit;
+                    if *bit {
+                        break;
+                    }
+                }
+            }
+            let instance = instance(Fp::from(x), &bits);
+
+            let mut circuit = MyCircuit::new(255, false);
+            circuit.forged = Some(bits.clone());
+            let prover = MockProver::run(10, &circuit, vec![instance.clone()]).unwrap();
+            prover.assert_satisfied();
+
+            circuit.strict = true;
+            let prover = MockProver::run(10, &circuit, vec![instance]).unwrap();
+            assert!(prover.verify().is_err(), "x = {}", x);
+        }
+    }
+
+    #[test]
+    fn mutation_bits() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let x = Fp::from(0b1011_0010);
+        let instance = instance(x, &le_bits(&x, 8));
+        let circuit = MyCircuit::new(8, false);
+        let report = fuzz(5, &circuit, vec![instance], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,380 @@
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+use std::ops::Deref;
+
+/// An assigned cell that is constrained to be 0 or 1.
+///
+/// Only chips that constrain their output to be boolean hand these out, so a function
+/// taking an `AssignedBit` does not need to check its input again.
+#[derive(Clone, Debug)]
+pub struct AssignedBit<F: FieldExt>(AssignedCell<F, F>);
+
+impl<F: FieldExt> AssignedBit<F> {
+    /// Wraps a cell that the caller has already constrained to be boolean.
+    pub(crate) fn new(cell: AssignedCell<F, F>) -> Self {
+        Self(cell)
+    }
+
+    pub fn bit(&self) -> Value<bool> {
+        self.0.value().map(|value| *value == F::one())
+    }
+}
+
+impl<F: FieldExt> Deref for AssignedBit<F> {
+    type Target = AssignedCell<F, F>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<F: FieldExt> From<AssignedBit<F>> for AssignedCell<F, F> {
+    fn from(bit: AssignedBit<F>) -> Self {
+        bit.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoolConfig {
+    pub advice: [Column<Advice>; 4],
+    q_bool: Selector,
+    q_and: Selector,
+    q_or: Selector,
+    q_xor: Selector,
+    q_not: Selector,
+    q_select: Selector,
+}
+
+/// Boolean logic over assigned cells. Every operation takes one row:
+///
+///   a  |  b  |  c  |     out
+///  ----+-----+-----+-------------
+///   x  |     |     |              assert_bool
+///   x  |  y  |     |   x op y     and, or, xor
+///   x  |     |     |   1 - x      not
+///  bit |  x  |  y  | bit ? x : y  select
+///
+#[derive(Debug, Clone)]
+pub struct BoolChip<F: FieldExt> {
+    config: BoolConfig,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> BoolChip<F> {
+    pub fn construct(config: BoolConfig) -> Self {
+        Self {
+            config,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> BoolConfig {
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let [a, b, c, out] = advice;
+
+        let q_bool = meta.selector();
+        let q_and = meta.selector();
+        let q_or = meta.selector();
+        let q_xor = meta.selector();
+        let q_not = meta.selector();
+        let q_select = meta.selector();
+
+        meta.create_gate("bool", |meta| {
+            let s = meta.query_selector(q_bool);
+            let a = meta.query_advice(a, Rotation::cur());
+            vec![s * a.clone() * (Expression::Constant(F::one()) - a)]
+        });
+
+        meta.create_gate("and", |meta| {
+            let s = meta.query_selector(q_and);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![s * (a * b - out)]
+        });
+
+        meta.create_gate("or", |meta| {
+            let s = meta.query_selector(q_or);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![s * (a.clone() + b.clone() - a * b - out)]
+        });
+
+        meta.create_gate("xor", |meta| {
+            let s = meta.query_selector(q_xor);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let two = Expression::Constant(F::from(2));
+            vec![s * (a.clone() + b.clone() - two * a * b - out)]
+        });
+
+        meta.create_gate("not", |meta| {
+            let s = meta.query_selector(q_not);
+            let a = meta.query_advice(a, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![s * (Expression::Constant(F::one()) - a - out)]
+        });
+
+        meta.create_gate("select", |meta| {
+            let s = meta.query_selector(q_select);
+            let bit = meta.query_advice(a, Rotation::cur());
+            let x = meta.query_advice(b, Rotation::cur());
+            let y = meta.query_advice(c, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            vec![s * (bit * (x - y.clone()) + y - out)]
+        });
+
+        BoolConfig {
+            advice: [a, b, c, out],
+            q_bool,
+            q_and,
+            q_or,
+            q_xor,
+            q_not,
+            q_select,
+        }
+    }
+
+    /// Constrains `cell` to be 0 or 1.
+    pub fn assert_bool(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: &AssignedCell<F, F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        layouter.assign_region(
+            || "assert_bool",
+            |mut region| {
+                self.config.q_bool.enable(&mut region, 0)?;
+                cell.copy_advice(|| "bit", &mut region, self.config.advice[0], 0)
+                    .map(AssignedBit)
+            },
+        )
+    }
+
+    pub fn and(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedBit<F>,
+        b: &AssignedBit<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.binary(layouter, "and", self.config.q_and, a, b, |a, b| a * b)
+    }
+
+    pub fn or(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedBit<F>,
+        b: &AssignedBit<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.binary(layouter, "or", self.config.q_or, a, b, |a, b| a + b - a * b)
+    }
+
+    pub fn xor(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedBit<F>,
+        b: &AssignedBit<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.binary(layouter, "xor", self.config.q_xor, a, b, |a, b| {
+            a + b - (a * b).double()
+        })
+    }
+
+    pub fn not(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedBit<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        layouter.assign_region(
+            || "not",
+            |mut region| {
+                self.config.q_not.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
+                let out = a.value().map(|a| F::one() - a);
+                region
+                    .assign_advice(|| "not", self.config.advice[3], 0, || out)
+                    .map(AssignedBit)
+            },
+        )
+    }
+
+    /// Returns the conjunction of all `bits`, which must not be empty.
+    pub fn and_many(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bits: &[AssignedBit<F>],
+    ) -> Result<AssignedBit<F>, Error> {
+        let (first, rest) = bits.split_first().ok_or(Error::Synthesis)?;
+        rest.iter().try_fold(first.clone(), |acc, bit| {
+            self.and(layouter.namespace(|| "and"), &acc, bit)
+        })
+    }
+
+    /// Returns `a` if `bit` is 1 and `b` if it is 0.
+    pub fn select(
+        &self,
+        mut layouter: impl Layouter<F>,
+        bit: &AssignedBit<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                self.config.q_select.enable(&mut region, 0)?;
+                bit.copy_advice(|| "bit", &mut region, self.config.advice[0], 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.advice[1], 0)?;
+                b.copy_advice(|| "b", &mut region, self.config.advice[2], 0)?;
+
+                let out = bit
+                    .bit()
+                    .zip(a.value().zip(b.value()))
+                    .map(|(bit, (a, b))| if bit { *a } else { *b });
+                region.assign_advice(|| "select", self.config.advice[3], 0, || out)
+            },
+        )
+    }
+
+    fn binary(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &'static str,
+        selector: Selector,
+        a: &AssignedBit<F>,
+        b: &AssignedBit<F>,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<AssignedBit<F>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.advice[0], 0)?;
+                b.copy_advice(|| "b", &mut region, self.config.advice[1], 0)?;
+                let out = a.value().zip(b.value()).map(|(a, b)| op(*a, *b));
+                region
+                    .assign_advice(|| name, self.config.advice[3], 0, || out)
+                    .map(AssignedBit)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Reads `x` and `y` from instance rows 0 and 1, and exposes
+    /// `[x & y, x | y, x ^ y, !x, x ? 10 : 20, x & y & 1]` at the rows after them.
+    #[derive(Default)]
+    struct MyCircuit;
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (BoolConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            (BoolChip::configure(meta, advice), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = BoolChip::construct(config.clone());
+            let column = config.advice[0];
+
+            let (x, y, one, ten, twenty) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let x = region.assign_advice_from_instance(|| "x", instance, 0, column, 0)?;
+                    let y = region.assign_advice_from_instance(|| "y", instance, 1, column, 1)?;
+                    let one = region.assign_advice_from_constant(|| "1", column, 2, Fp::one())?;
+                    let ten =
+                        region.assign_advice_from_constant(|| "10", column, 3, Fp::from(10))?;
+                    let twenty =
+                        region.assign_advice_from_constant(|| "20", column, 4, Fp::from(20))?;
+                    Ok((x, y, one, ten, twenty))
+                },
+            )?;
+
+            let x = chip.assert_bool(layouter.namespace(|| "x"), &x)?;
+            let y = chip.assert_bool(layouter.namespace(|| "y"), &y)?;
+            let one = chip.assert_bool(layouter.namespace(|| "one"), &one)?;
+
+            let outputs = [
+                chip.and(layouter.namespace(|| "x & y"), &x, &y)?.into(),
+                chip.or(layouter.namespace(|| "x | y"), &x, &y)?.into(),
+                chip.xor(layouter.namespace(|| "x ^ y"), &x, &y)?.into(),
+                chip.not(layouter.namespace(|| "!x"), &x)?.into(),
+                chip.select(layouter.namespace(|| "x ? 10 : 20"), &x, &ten, &twenty)?,
+                chip.and_many(layouter.namespace(|| "x & y & 1"), &[x, y, one])?
+                    .into(),
+            ];
+            for (row, output) in outputs.iter().enumerate() {
+                layouter.constrain_instance(output.cell(), instance, row + 2)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn instance(x: u64, y: u64) -> Vec<Fp> {
+        let select = if x == 1 { 10 } else { 20 };
+        [x, y, x & y, x | y, x ^ y, 1 - x, select, x & y]
+            .map(Fp::from)
+            .to_vec()
+    }
+
+    #[test]
+    fn bool_ops() {
+        for x in 0..2 {
+            for y in 0..2 {
+                let instance = instance(x, y);
+                let prover = MockProver::run(5, &MyCircuit, vec![instance.clone()]).unwrap();
+                prover.assert_satisfied();
+
+                for row in 2..instance.len() {
+                    let mut wrong = instance.clone();
+                    wrong[row] += Fp::one();
+                    let prover = MockProver::run(5, &MyCircuit, vec![wrong]).unwrap();
+                    assert!(
+                        prover.verify().is_err(),
+                        "x = {}, y = {}, row {}",
+                        x,
+                        y,
+                        row
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bool_rejects_non_boolean_input() {
+        let mut instance = instance(1, 0);
+        instance[0] = Fp::from(2);
+        let prover = MockProver::run(5, &MyCircuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn mutation_bool_ops() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let report = fuzz(5, &MyCircuit, vec![instance(1, 0)], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
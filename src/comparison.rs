@@ -0,0 +1,368 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    boolean::AssignedBit,
+    is_zero::{IsZeroChip, IsZeroConfig},
+    range_check::example2::RangeTableConfig,
+};
+
+/// The cells holding `a < b` and `a <= b`.
+type Comparison<F> = (AssignedBit<F>, AssignedBit<F>);
+
+/// This helper compares two values of at most `BITS` bits and returns `a < b` or
+/// `a <= b` as an assigned boolean.
+///
+/// `a` and `b` are decomposed into limbs of `log2(LOOKUP_RANGE)` bits, each looked up
+/// in a table of `0..LOOKUP_RANGE`, so inputs with more than `BITS` bits are refused.
+/// The bit `lt` is pinned by decomposing `diff = a - b + lt * 2^BITS` the same way:
+/// `diff` only fits in `BITS` bits if `lt` is 1 exactly when `a < b`. An `IsZeroChip`
+/// on `a - b` then gives `le = lt + (a == b)`.
+///
+///   a | b | lt | le | value_inv | a_limbs | b_limbs | diff_limbs | q_compare | q_limb | q_top_limb
+///  --------------------------------------------------------------------------------------------
+///   a | b | lt | le |     *     |   a_0   |   b_0   |   diff_0   |     1     |   1    |     0
+///     |   |    |    |           |   a_1   |   b_1   |   diff_1   |     0     |   1    |     1
+///
+/// The top limb is also looked up shifted left by the bits it must not use, so that
+/// `BITS` does not have to be a multiple of the limb size.
+#[derive(Debug, Clone)]
+pub struct ComparisonConfig<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize> {
+    q_compare: Selector,
+    q_limb: Selector,
+    q_top_limb: Selector,
+    a: Column<Advice>,
+    b: Column<Advice>,
+    lt: Column<Advice>,
+    le: Column<Advice>,
+    a_equals_b: IsZeroConfig<F>,
+    limbs: [Column<Advice>; 3],
+    pub table: RangeTableConfig<F, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize>
+    ComparisonConfig<F, BITS, LOOKUP_RANGE>
+{
+    fn limb_bits() -> usize {
+        LOOKUP_RANGE.trailing_zeros() as usize
+    }
+
+    fn num_limbs() -> usize {
+        BITS.div_ceil(Self::limb_bits())
+    }
+
+    /// Returns `2^BITS`.
+    fn bound() -> F {
+        F::from_u128(1 << BITS)
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        assert!(LOOKUP_RANGE > 1 && LOOKUP_RANGE.is_power_of_two());
+        assert!(BITS > 0 && BITS < 128);
+
+        let q_compare = meta.selector();
+        let q_limb = meta.complex_selector();
+        let q_top_limb = meta.complex_selector();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let lt = meta.advice_column();
+        let le = meta.advice_column();
+        let value_inv = meta.advice_column();
+        let limbs = [(); 3].map(|_| meta.advice_column());
+        let table = RangeTableConfig::configure(meta);
+
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(lt);
+        meta.enable_equality(le);
+
+        let a_equals_b = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_compare),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("compare", |meta| {
+            let q = meta.query_selector(q_compare);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let le = meta.query_advice(le, Rotation::cur());
+
+            // Given a limb column, returns the expression
+            // limb_0 + limb_1 * 2^limb_bits + limb_2 * 2^(2 * limb_bits) + ...
+            let compose = |meta: &mut halo2_proofs::plonk::VirtualCells<'_, F>, column| {
+                (0..Self::num_limbs())
+                    .rev()
+                    .fold(Expression::Constant(F::zero()), |expr, i| {
+                        let limb = meta.query_advice(column, Rotation(i as i32));
+                        expr * Expression::Constant(F::from(LOOKUP_RANGE as u64)) + limb
+                    })
+            };
+            let [a_limbs, b_limbs, diff_limbs] = limbs.map(|column| compose(meta, column));
+
+            Constraints::with_selector(
+                q,
+                [
+                    ("a fits in BITS bits", a.clone() - a_limbs),
+                    ("b fits in BITS bits", b.clone() - b_limbs),
+                    (
+                        "lt is boolean",
+                        lt.clone() * (Expression::Constant(F::one()) - lt.clone()),
+                    ),
+                    (
+                        "a - b + lt * 2^BITS fits in BITS bits",
+                        a - b + lt.clone() * Expression::Constant(Self::bound()) - diff_limbs,
+                    ),
+                    ("le = lt + (a == b)", le - (lt + a_equals_b.expr())),
+                ],
+            )
+        });
+
+        let top_bits = BITS - (Self::num_limbs() - 1) * Self::limb_bits();
+        let top_shift = F::from(1 << (Self::limb_bits() - top_bits));
+        for column in limbs {
+            meta.lookup(|meta| {
+                let q_limb = meta.query_selector(q_limb);
+                let limb = meta.query_advice(column, Rotation::cur());
+
+                vec![(q_limb * limb, table.value)]
+            });
+            meta.lookup(|meta| {
+                let q_top_limb = meta.query_selector(q_top_limb);
+                let limb = meta.query_advice(column, Rotation::cur());
+
+                vec![(
+                    q_top_limb * limb * Expression::Constant(top_shift),
+                    table.value,
+                )]
+            });
+        }
+
+        Self {
+            q_compare,
+            q_limb,
+            q_top_limb,
+            a,
+            b,
+            lt,
+            le,
+            a_equals_b,
+            limbs,
+            table,
+        }
+    }
+
+    /// Returns a cell holding 1 if `a < b` and 0 otherwise.
+    pub fn lt(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.compare(layouter, a, b).map(|(lt, _)| lt)
+    }
+
+    /// Returns a cell holding 1 if `a <= b` and 0 otherwise.
+    pub fn le(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        self.compare(layouter, a, b).map(|(_, le)| le)
+    }
+
+    fn compare(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<Comparison<F>, Error> {
+        let is_zero_chip = IsZeroChip::construct(self.a_equals_b.clone());
+
+        layouter.assign_region(
+            || "compare",
+            |mut region| {
+                let num_limbs = Self::num_limbs();
+
+                self.q_compare.enable(&mut region, 0)?;
+                for row in 0..num_limbs {
+                    self.q_limb.enable(&mut region, row)?;
+                }
+                self.q_top_limb.enable(&mut region, num_limbs - 1)?;
+
+                let a = a.copy_advice(|| "a", &mut region, self.a, 0)?;
+                let b = b.copy_advice(|| "b", &mut region, self.b, 0)?;
+                let a = a.value().copied();
+                let b = b.value().copied();
+
+                // Out-of-range inputs get a truncated decomposition, which the
+                // "compare" gate then rejects.
+                let lt = a.zip(b).map(|(a, b)| {
+                    if a.get_lower_128() < b.get_lower_128() {
+                        F::one()
+                    } else {
+                        F::zero()
+                    }
+                });
+                let diff = a
+                    .zip(b)
+                    .zip(lt)
+                    .map(|((a, b), lt)| a - b + lt * Self::bound());
+                for (column, value) in self.limbs.iter().zip([a, b, diff]) {
+                    for row in 0..num_limbs {
+                        let limb = value.map(|value| {
+                            let shifted = value.get_lower_128() >> (row * Self::limb_bits());
+                            F::from_u128(shifted % LOOKUP_RANGE as u128)
+                        });
+                        region.assign_advice(|| "limb", *column, row, || limb)?;
+                    }
+                }
+
+                is_zero_chip.assign(&mut region, 0, a - b)?;
+                let le = a
+                    .zip(b)
+                    .zip(lt)
+                    .map(|((a, b), lt)| if a == b { lt + F::one() } else { lt });
+
+                let lt = region.assign_advice(|| "a < b", self.lt, 0, || lt)?;
+                let le = region.assign_advice(|| "a <= b", self.le, 0, || le)?;
+                Ok((AssignedBit::new(lt), AssignedBit::new(le)))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    /// Reads `a` and `b` from instance rows 0 and 1, and exposes `a < b` and
+    /// `a <= b` at instance rows 2 and 3.
+    #[derive(Default)]
+    struct MyCircuit<const BITS: usize, const LOOKUP_RANGE: usize>;
+
+    impl<const BITS: usize, const LOOKUP_RANGE: usize> Circuit<Fp> for MyCircuit<BITS, LOOKUP_RANGE> {
+        type Config = (
+            ComparisonConfig<Fp, BITS, LOOKUP_RANGE>,
+            Column<Advice>,
+            Column<Instance>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (ComparisonConfig::configure(meta), advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            let (a, b) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice_from_instance(|| "a", instance, 0, advice, 0)?;
+                    let b = region.assign_advice_from_instance(|| "b", instance, 1, advice, 1)?;
+                    Ok((a, b))
+                },
+            )?;
+
+            let lt = config.lt(layouter.namespace(|| "lt"), &a, &b)?;
+            let le = config.le(layouter.namespace(|| "le"), &a, &b)?;
+            layouter.constrain_instance(lt.cell(), instance, 2)?;
+            layouter.constrain_instance(le.cell(), instance, 3)
+        }
+    }
+
+    fn verifies<const BITS: usize, const LOOKUP_RANGE: usize>(
+        a: Fp,
+        b: Fp,
+        lt: bool,
+        le: bool,
+    ) -> bool {
+        let instance = vec![a, b, Fp::from(lt as u64), Fp::from(le as u64)];
+        MockProver::run(6, &MyCircuit::<BITS, LOOKUP_RANGE>, vec![instance])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    /// Checks every pair of `BITS`-bit values, with the correct and the flipped
+    /// outputs.
+    fn exhaustive<const BITS: usize, const LOOKUP_RANGE: usize>() {
+        for a in 0..1u64 << BITS {
+            for b in 0..1u64 << BITS {
+                let (x, y) = (Fp::from(a), Fp::from(b));
+                assert!(verifies::<BITS, LOOKUP_RANGE>(x, y, a < b, a <= b));
+                assert!(!verifies::<BITS, LOOKUP_RANGE>(x, y, a >= b, a <= b));
+                assert!(!verifies::<BITS, LOOKUP_RANGE>(x, y, a < b, a > b));
+            }
+        }
+    }
+
+    #[test]
+    fn compare_exhaustive_4_bits() {
+        exhaustive::<4, 4>();
+    }
+
+    #[test]
+    fn compare_exhaustive_3_bits_with_partial_top_limb() {
+        exhaustive::<3, 4>();
+    }
+
+    #[test]
+    fn compare_exhaustive_5_bits_with_single_limb() {
+        exhaustive::<5, 32>();
+    }
+
+    #[test]
+    fn compare_rejects_out_of_range_inputs() {
+        let too_big = Fp::from(16);
+        let negative = -Fp::one();
+        for (a, b) in [
+            (too_big, Fp::zero()),
+            (Fp::zero(), too_big),
+            (negative, Fp::zero()),
+            (Fp::from(3), negative),
+        ] {
+            for lt in [false, true] {
+                for le in [false, true] {
+                    assert!(!verifies::<4, 4>(a, b, lt, le));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mutation_compare() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let instance = vec![Fp::from(5), Fp::from(11), Fp::one(), Fp::one()];
+        let report = fuzz(6, &MyCircuit::<4, 4>, vec![instance], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,326 @@
+use crate::{
+    boolean::AssignedBit,
+    is_zero::{IsZeroChip, IsZeroConfig},
+};
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Clone, Debug)]
+pub struct DivConfig<F> {
+    pub advice: [Column<Advice>; 5],
+    q_div: Selector,
+    q_nonzero: Selector,
+    q_inverse: Selector,
+    b_is_zero: IsZeroConfig<F>,
+}
+
+/// The result of [`DivChip::div`].
+#[derive(Clone, Debug)]
+pub struct Quotient<F: FieldExt> {
+    /// `a / b`, or 0 if `b` is 0.
+    pub quotient: AssignedCell<F, F>,
+    pub divisor_is_zero: AssignedBit<F>,
+}
+
+/// Field division and inversion with an explicit result for zero.
+///
+///   a  |  b  | quotient | b_inv | b_is_zero | q_div | q_nonzero | q_inverse
+/// -----+-----+----------+-------+-----------+-------+-----------+----------
+///   a  |  b  |  a / b   |  1/b  |  b == 0   |   1   |  checked  |    0
+///      |  x  |          |  1/x  |           |   0   |     0     |    1
+///
+/// `div` pins the quotient with `(1 - b_is_zero) * (quotient * b - a) = 0` and
+/// `b_is_zero * quotient = 0`, so it is `a / b` or 0 and never free. `div_checked`
+/// also enables "divisor is not zero", which is the constraint that fails in the
+/// "div" region when `b` is 0.
+pub struct DivChip<F: FieldExt> {
+    config: DivConfig<F>,
+}
+
+impl<F: FieldExt> DivChip<F> {
+    pub fn construct(config: DivConfig<F>) -> Self {
+        DivChip { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 5]) -> DivConfig<F> {
+        let [a, b, quotient, b_inv, b_is_zero] = advice;
+        let q_div = meta.selector();
+        let q_nonzero = meta.selector();
+        let q_inverse = meta.selector();
+
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(quotient);
+        meta.enable_equality(b_inv);
+        meta.enable_equality(b_is_zero);
+
+        let is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_div),
+            |meta| meta.query_advice(b, Rotation::cur()),
+            b_inv,
+        );
+
+        meta.create_gate("div", |meta| {
+            let s = meta.query_selector(q_div);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let quotient = meta.query_advice(quotient, Rotation::cur());
+            let b_is_zero = meta.query_advice(b_is_zero, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                s,
+                [
+                    ("b_is_zero", b_is_zero.clone() - is_zero.expr()),
+                    (
+                        "quotient * b = a",
+                        (one - b_is_zero.clone()) * (quotient.clone() * b - a),
+                    ),
+                    ("quotient is 0 if b is 0", b_is_zero * quotient),
+                ],
+            )
+        });
+
+        meta.create_gate("divisor is not zero", |meta| {
+            let s = meta.query_selector(q_nonzero);
+            let b_is_zero = meta.query_advice(b_is_zero, Rotation::cur());
+            Constraints::with_selector(s, [("divisor is not zero", b_is_zero)])
+        });
+
+        meta.create_gate("inverse_or_zero", |meta| {
+            //
+            // x * (1 - x * x_inv) = 0 makes x_inv the inverse of a nonzero x, and
+            // x_inv * (1 - x * x_inv) = 0 makes it 0 if x is 0.
+            //
+            let s = meta.query_selector(q_inverse);
+            let x = meta.query_advice(b, Rotation::cur());
+            let x_inv = meta.query_advice(b_inv, Rotation::cur());
+            let is_zero = Expression::Constant(F::one()) - x.clone() * x_inv.clone();
+
+            Constraints::with_selector(
+                s,
+                [
+                    ("x_inv is the inverse of x", x * is_zero.clone()),
+                    ("x_inv is 0 if x is 0", x_inv * is_zero),
+                ],
+            )
+        });
+
+        DivConfig {
+            advice,
+            q_div,
+            q_nonzero,
+            q_inverse,
+            b_is_zero: is_zero,
+        }
+    }
+
+    /// Returns `a / b` and whether `b` is 0, in which case the quotient is 0.
+    pub fn div(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<Quotient<F>, Error> {
+        self.assign_div(layouter, a, b, false)
+    }
+
+    /// Returns `a / b`, failing the constraint "divisor is not zero" if `b` is 0.
+    pub fn div_checked(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        self.assign_div(layouter, a, b, true)
+            .map(|quotient| quotient.quotient)
+    }
+
+    fn assign_div(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        checked: bool,
+    ) -> Result<Quotient<F>, Error> {
+        let [a_column, b_column, quotient_column, _, b_is_zero_column] = self.config.advice;
+        let is_zero_chip = IsZeroChip::construct(self.config.b_is_zero.clone());
+
+        layouter.assign_region(
+            || "div",
+            |mut region| {
+                self.config.q_div.enable(&mut region, 0)?;
+                if checked {
+                    self.config.q_nonzero.enable(&mut region, 0)?;
+                }
+                a.copy_advice(|| "a", &mut region, a_column, 0)?;
+                b.copy_advice(|| "b", &mut region, b_column, 0)?;
+                is_zero_chip.assign(&mut region, 0, b.value().copied())?;
+
+                let b_inv = b.value().map(|b| b.invert().unwrap_or(F::zero()));
+                let quotient = a.value().zip(b_inv).map(|(a, b_inv)| *a * b_inv);
+                let quotient =
+                    region.assign_advice(|| "quotient", quotient_column, 0, || quotient)?;
+
+                let b_is_zero = b.value().map(|b| F::from((*b == F::zero()) as u64));
+                let divisor_is_zero =
+                    region.assign_advice(|| "b == 0", b_is_zero_column, 0, || b_is_zero)?;
+
+                Ok(Quotient {
+                    quotient,
+                    divisor_is_zero: AssignedBit::new(divisor_is_zero),
+                })
+            },
+        )
+    }
+
+    /// Returns `1 / x`, or 0 if `x` is 0.
+    pub fn inverse_or_zero(
+        &self,
+        mut layouter: impl Layouter<F>,
+        x: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [_, x_column, _, x_inv_column, _] = self.config.advice;
+
+        layouter.assign_region(
+            || "inverse_or_zero",
+            |mut region| {
+                self.config.q_inverse.enable(&mut region, 0)?;
+                x.copy_advice(|| "x", &mut region, x_column, 0)?;
+                let x_inv = x.value().map(|x| x.invert().unwrap_or(F::zero()));
+                region.assign_advice(|| "x_inv", x_inv_column, 0, || x_inv)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{arithmetic::Field, dev::MockProver, pasta::Fp};
+
+    /// Reads `a` and `b` from instance rows 0 and 1, and exposes `a / b`, `b == 0` and
+    /// `inverse_or_zero(b)` at rows 2 to 4.
+    #[derive(Default)]
+    struct MyCircuit {
+        checked: bool,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (DivConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                checked: self.checked,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 5].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (DivChip::configure(meta, advice), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let column = config.advice[0];
+            let (a, b) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice_from_instance(|| "a", instance, 0, column, 0)?;
+                    let b = region.assign_advice_from_instance(|| "b", instance, 1, column, 1)?;
+                    Ok((a, b))
+                },
+            )?;
+
+            let chip = DivChip::construct(config);
+            if self.checked {
+                let quotient = chip.div_checked(layouter.namespace(|| "a / b"), &a, &b)?;
+                return layouter.constrain_instance(quotient.cell(), instance, 2);
+            }
+
+            let quotient = chip.div(layouter.namespace(|| "a / b"), &a, &b)?;
+            let b_inv = chip.inverse_or_zero(layouter.namespace(|| "1 / b"), &b)?;
+            layouter.constrain_instance(quotient.quotient.cell(), instance, 2)?;
+            layouter.constrain_instance(quotient.divisor_is_zero.cell(), instance, 3)?;
+            layouter.constrain_instance(b_inv.cell(), instance, 4)
+        }
+    }
+
+    fn instance(a: u64, b: u64) -> Vec<Fp> {
+        let (a, b) = (Fp::from(a), Fp::from(b));
+        let b_inv = b.invert().unwrap_or(Fp::zero());
+        let b_is_zero = Fp::from((b == Fp::zero()) as u64);
+        vec![a, b, a * b_inv, b_is_zero, b_inv]
+    }
+
+    #[test]
+    fn div() {
+        for (a, b) in [(10, 5), (7, 3), (0, 9), (5, 0), (0, 0)] {
+            let instance = instance(a, b);
+            let prover = MockProver::run(4, &MyCircuit::default(), vec![instance.clone()]);
+            prover.unwrap().assert_satisfied();
+            if b != 0 {
+                assert_eq!(instance[2] * instance[1], instance[0]);
+            }
+
+            for row in 2..instance.len() {
+                let mut wrong = instance.clone();
+                wrong[row] += Fp::one();
+                let prover = MockProver::run(4, &MyCircuit::default(), vec![wrong]).unwrap();
+                assert!(
+                    prover.verify().is_err(),
+                    "a = {}, b = {}, row {}",
+                    a,
+                    b,
+                    row
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn div_checked_rejects_zero_divisor() {
+        let circuit = MyCircuit { checked: true };
+
+        let prover = MockProver::run(4, &circuit, vec![instance(10, 5)]).unwrap();
+        prover.assert_satisfied();
+
+        let prover = MockProver::run(4, &circuit, vec![instance(10, 0)]).unwrap();
+        let failures = prover.verify().unwrap_err();
+        assert_eq!(failures.len(), 1);
+        let failure = failures[0].to_string();
+        assert!(failure.contains("divisor is not zero"), "{}", failure);
+        assert!(failure.contains("Region 1 ('div')"), "{}", failure);
+    }
+
+    #[test]
+    fn mutation_div() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        for (a, b) in [(10, 5), (10, 0)] {
+            let report = fuzz(
+                4,
+                &MyCircuit::default(),
+                vec![instance(a, b)],
+                Mutations::Pairs,
+            );
+            let report = report.unwrap();
+            // `b_inv` of the "div" region is only read by `IsZeroChip`, which leaves it
+            // free when b is 0.
+            let free: Vec<_> = report
+                .free_cells
+                .iter()
+                .map(|cell| cell.annotation.as_str())
+                .collect();
+            let expected: &[&str] = if b == 0 { &["value inv"] } else { &[] };
+            assert_eq!(free, expected, "{}", report);
+            assert!(report.free_pairs.is_empty(), "{}", report);
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::comparison::ComparisonConfig;
+
+/// The result of [`DivModConfig::divmod`].
+#[derive(Clone, Debug)]
+pub struct DivMod<F: FieldExt> {
+    pub quotient: AssignedCell<F, F>,
+    pub remainder: AssignedCell<F, F>,
+}
+
+/// This helper divides `BITS`-bit unsigned integers, returning the quotient and the
+/// remainder.
+///
+///   a  |  d  |  q  |  r  | q_divmod
+///  ----+-----+-----+-----+----------
+///   a  |  d  |  q  |  r  |    1          a = q * d + r
+///
+/// On its own that equation has many solutions in the field, so the comparison chip
+/// also proves `r < d` and `q <= a`. Besides ordering its inputs, the comparison
+/// range-checks all four values to `BITS` bits with the lookup table of
+/// `range_check/example2`, so `q * d + r` cannot wrap around the modulus and `(q, r)`
+/// is the integer division of `a` by `d`. A zero `d` fails `r < d`.
+///
+///  r < d | q <= a | q_bounds
+/// -------+--------+----------
+///    1   |    1   |    1
+#[derive(Debug, Clone)]
+pub struct DivModConfig<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize> {
+    q_divmod: Selector,
+    q_bounds: Selector,
+    a: Column<Advice>,
+    d: Column<Advice>,
+    q: Column<Advice>,
+    r: Column<Advice>,
+    pub comparison: ComparisonConfig<F, BITS, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize>
+    DivModConfig<F, BITS, LOOKUP_RANGE>
+{
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        // q * d + r must stay below the modulus.
+        assert!(2 * BITS < F::NUM_BITS as usize - 1);
+
+        let q_divmod = meta.selector();
+        let q_bounds = meta.selector();
+        let [a, d, q, r] = [(); 4].map(|_| meta.advice_column());
+        for column in [a, d, q, r] {
+            meta.enable_equality(column);
+        }
+        let comparison = ComparisonConfig::configure(meta);
+
+        meta.create_gate("divmod", |meta| {
+            let s = meta.query_selector(q_divmod);
+            let a = meta.query_advice(a, Rotation::cur());
+            let d = meta.query_advice(d, Rotation::cur());
+            let q = meta.query_advice(q, Rotation::cur());
+            let r = meta.query_advice(r, Rotation::cur());
+            Constraints::with_selector(s, [("a = q * d + r", q * d + r - a)])
+        });
+
+        meta.create_gate("divmod bounds", |meta| {
+            // The comparison bits are copied into the a and d columns.
+            let s = meta.query_selector(q_bounds);
+            let r_lt_d = meta.query_advice(a, Rotation::cur());
+            let q_le_a = meta.query_advice(d, Rotation::cur());
+            let one = || Expression::Constant(F::one());
+            Constraints::with_selector(s, [("r < d", one() - r_lt_d), ("q <= a", one() - q_le_a)])
+        });
+
+        Self {
+            q_divmod,
+            q_bounds,
+            a,
+            d,
+            q,
+            r,
+            comparison,
+        }
+    }
+
+    /// Returns `a / d` and `a % d`.
+    pub fn divmod(
+        &self,
+        layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        d: &AssignedCell<F, F>,
+    ) -> Result<DivMod<F>, Error> {
+        let quotient_and_remainder = a.value().zip(d.value()).map(|(a, d)| {
+            let (a, d) = (a.get_lower_128(), d.get_lower_128());
+            match d {
+                0 => (F::zero(), F::from_u128(a)),
+                d => (F::from_u128(a / d), F::from_u128(a % d)),
+            }
+        });
+        let (q, r) = quotient_and_remainder.unzip();
+        self.assign(layouter, a, d, q, r)
+    }
+
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        d: &AssignedCell<F, F>,
+        q: Value<F>,
+        r: Value<F>,
+    ) -> Result<DivMod<F>, Error> {
+        let (quotient, remainder) = layouter.assign_region(
+            || "divmod",
+            |mut region| {
+                self.q_divmod.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.a, 0)?;
+                d.copy_advice(|| "d", &mut region, self.d, 0)?;
+                let q = region.assign_advice(|| "q", self.q, 0, || q)?;
+                let r = region.assign_advice(|| "r", self.r, 0, || r)?;
+                Ok((q, r))
+            },
+        )?;
+
+        let r_lt_d = self
+            .comparison
+            .lt(layouter.namespace(|| "r < d"), &remainder, d)?;
+        let q_le_a = self
+            .comparison
+            .le(layouter.namespace(|| "q <= a"), &quotient, a)?;
+        layouter.assign_region(
+            || "divmod bounds",
+            |mut region| {
+                self.q_bounds.enable(&mut region, 0)?;
+                r_lt_d.copy_advice(|| "r < d", &mut region, self.a, 0)?;
+                q_le_a.copy_advice(|| "q <= a", &mut region, self.d, 0)?;
+                Ok(())
+            },
+        )?;
+
+        Ok(DivMod {
+            quotient,
+            remainder,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    /// Reads `a` and `d` from instance rows 0 and 1, and exposes `a / d` and `a % d`
+    /// at rows 2 and 3.
+    #[derive(Default)]
+    struct MyCircuit {
+        /// Witnesses this quotient and remainder instead of the correct ones.
+        forged: Option<(Fp, Fp)>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (DivModConfig<Fp, 4, 4>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                forged: self.forged,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            (DivModConfig::configure(meta), advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.comparison.table.load(&mut layouter)?;
+
+            let (a, d) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice_from_instance(|| "a", instance, 0, advice, 0)?;
+                    let d = region.assign_advice_from_instance(|| "d", instance, 1, advice, 1)?;
+                    Ok((a, d))
+                },
+            )?;
+
+            let result = match self.forged {
+                None => config.divmod(layouter.namespace(|| "divmod"), &a, &d)?,
+                Some((q, r)) => config.assign(
+                    layouter.namespace(|| "divmod"),
+                    &a,
+                    &d,
+                    Value::known(q),
+                    Value::known(r),
+                )?,
+            };
+            layouter.constrain_instance(result.quotient.cell(), instance, 2)?;
+            layouter.constrain_instance(result.remainder.cell(), instance, 3)
+        }
+    }
+
+    fn verifies(circuit: &MyCircuit, a: u64, d: u64, q: Fp, r: Fp) -> bool {
+        let instance = vec![Fp::from(a), Fp::from(d), q, r];
+        MockProver::run(6, circuit, vec![instance])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn divmod_exhaustive_4_bits() {
+        let circuit = MyCircuit::default();
+        for a in 0..16 {
+            for d in 1..16 {
+                let (q, r) = (Fp::from(a / d), Fp::from(a % d));
+                assert!(verifies(&circuit, a, d, q, r), "{} / {}", a, d);
+                assert!(!verifies(&circuit, a, d, q + Fp::one(), r));
+                assert!(!verifies(&circuit, a, d, q, r + Fp::one()));
+            }
+        }
+    }
+
+    #[test]
+    fn divmod_rejects_zero_divisor() {
+        let circuit = MyCircuit::default();
+        assert!(!verifies(&circuit, 7, 0, Fp::zero(), Fp::from(7)));
+    }
+
+    #[test]
+    fn divmod_rejects_forged_witness() {
+        // (q, r) pairs with a = q * d + r in the field, for a = 13 and d = 4.
+        let d_inv = Fp::from(4).invert().unwrap();
+        let forgeries = [
+            // r >= d
+            (Fp::from(2), Fp::from(5)),
+            (Fp::zero(), Fp::from(13)),
+            // r "negative"
+            (Fp::from(4), -Fp::from(3)),
+            // q wraps around the modulus
+            (Fp::from(13) * d_inv, Fp::zero()),
+            (Fp::from(11) * d_inv, Fp::from(2)),
+        ];
+        for (q, r) in forgeries {
+            assert_eq!(q * Fp::from(4) + r, Fp::from(13));
+            let circuit = MyCircuit {
+                forged: Some((q, r)),
+            };
+            assert!(!verifies(&circuit, 13, 4, q, r), "q = {:?}, r = {:?}", q, r);
+        }
+
+        let circuit = MyCircuit {
+            forged: Some((Fp::from(3), Fp::one())),
+        };
+        assert!(verifies(&circuit, 13, 4, Fp::from(3), Fp::one()));
+    }
+
+    #[test]
+    fn mutation_divmod() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let instance = vec![Fp::from(13), Fp::from(4), Fp::from(3), Fp::one()];
+        let report = fuzz(6, &MyCircuit::default(), vec![instance], Mutations::Single).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -1,4 +1,4 @@
-mod example1;
-mod example2;
-mod example3;
-mod example4;
\ No newline at end of file
+pub mod example1;
+pub mod example2;
+pub mod example3;
+pub mod example4;
@@ -2,7 +2,7 @@ use std::marker::PhantomData;
 use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
 
 #[derive(Debug, Clone)]
-struct FibonacciConfig {
+pub struct FibonacciConfig {
     pub col_a: Column<Advice>,
     pub col_b: Column<Advice>,
     pub col_c: Column<Advice>,
@@ -11,7 +11,7 @@ struct FibonacciConfig {
 }
 
 #[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
+pub struct FibonacciChip<F: FieldExt> {
     config: FibonacciConfig,
     _marker: PhantomData<F>,
 }
@@ -140,15 +140,35 @@ impl<F: FieldExt> FibonacciChip<F> {
     }
 }
 
-#[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+/// Starting from `f(0)` and `f(1)` at instance rows 0 and 1, proves that
+/// `f(nrows - 1)` is the value at instance row 2.
+pub struct MyCircuit<F> {
+    pub nrows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F> MyCircuit<F> {
+    pub fn new(nrows: usize) -> Self {
+        assert!(nrows >= 3);
+        Self {
+            nrows,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F> Default for MyCircuit<F> {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FibonacciConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self::new(self.nrows)
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -165,7 +185,7 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
         let (_, mut prev_b, mut prev_c) =
             chip.assign_first_row(layouter.namespace(|| "first row"))?;
 
-        for _i in 3..10 {
+        for _i in 3..self.nrows {
             let c_cell = 
                 chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
                 
@@ -181,10 +201,9 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 
 #[cfg(test)]
 mod tests {
-    use std::marker::PhantomData;
-
     use super::MyCircuit;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use proptest::prelude::*;
 
     #[test]
     fn fibonacci_example1() {
@@ -194,7 +213,7 @@ mod tests {
         let b = Fp::from(1); // F[1]
         let out = Fp::from(55); // F[9]
 
-        let circuit = MyCircuit(PhantomData);
+        let circuit = MyCircuit::new(10);
 
         let mut public_input = vec![a, b, out];
 
@@ -202,6 +221,66 @@ mod tests {
         prover.assert_satisfied();
     }
 
+    /// Native reference: the `n`-th term of the sequence starting at `f0, f1`.
+    fn fibonacci(f0: u64, f1: u64, n: usize) -> u64 {
+        let (mut a, mut b) = (f0, f1);
+        for _ in 0..n {
+            (a, b) = (b, a + b);
+        }
+        a
+    }
+
+    proptest! {
+        #[test]
+        fn fibonacci_example1_matches_reference(
+            f0 in 0u64..1 << 16,
+            f1 in 0u64..1 << 16,
+            nrows in 3usize..=20,
+        ) {
+            let circuit = MyCircuit::<Fp>::new(nrows);
+            let out = Fp::from(fibonacci(f0, f1, nrows - 1));
+
+            let public_input = vec![Fp::from(f0), Fp::from(f1), out];
+            let prover = MockProver::run(5, &circuit, vec![public_input]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            let public_input = vec![Fp::from(f0), Fp::from(f1), out + Fp::one()];
+            let prover = MockProver::run(5, &circuit, vec![public_input]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn regression_fibonacci_example1() {
+        let circuit = MyCircuit::<Fp>::new(10);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+
+        crate::regression::assert_vk_fingerprint("fibonacci_example1", 4, &circuit);
+        crate::regression::assert_golden_proof(
+            "fibonacci_example1",
+            4,
+            &circuit,
+            &[public_input],
+        );
+    }
+
+    #[test]
+    fn under_constrained_fibonacci_example1() {
+        let circuit = MyCircuit::<Fp>::new(10);
+        let report = crate::analysis::under_constrained::analyze(4, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_fibonacci_example1() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::<Fp>::new(10);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let report = fuzz(4, &circuit, vec![public_input], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_fibonacci1() {
@@ -211,7 +290,7 @@ mod tests {
         root.fill(&WHITE).unwrap();
         let root = root.titled("Fib 1 Layout", ("sans-serif", 60)).unwrap();
 
-        let circuit = MyCircuit::<Fp>(PhantomData);
+        let circuit = MyCircuit::<Fp>::new(10);
         halo2_proofs::dev::CircuitLayout::default()
             .render(4, &circuit, &root)
             .unwrap();
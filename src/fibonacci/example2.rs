@@ -5,14 +5,14 @@ use std::marker::PhantomData;
 struct ACell<F: FieldExt>(AssignedCell<F, F>);
 
 #[derive(Debug, Clone)]
-struct FibonacciConfig {
+pub struct FibonacciConfig {
     advice: Column<Advice>,
     selector: Selector,
     instance: Column<Instance>,
 }
 
 #[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
+pub struct FibonacciChip<F: FieldExt> {
     config: FibonacciConfig,
     _marker: PhantomData<F>,
 }
@@ -64,8 +64,11 @@ impl<F: FieldExt> FibonacciChip<F> {
         layouter.assign_region(
             || "entire fibonacci table",
             |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                self.config.selector.enable(&mut region, 1)?;
+                // The gate at `row` reads rows `row..row + 3`, so it is enabled on
+                // every row but the last two.
+                for row in 0..nrows - 2 {
+                    self.config.selector.enable(&mut region, row)?;
+                }
 
                 let mut a_cell = region.assign_advice_from_instance(
                     || "1",
@@ -83,10 +86,6 @@ impl<F: FieldExt> FibonacciChip<F> {
                 )?;
 
                 for row in 2..nrows {
-                    if row < nrows - 2 {
-                        self.config.selector.enable(&mut region, row)?;
-                    }
-
                     let c_cell = region.assign_advice(
                         || "advice",
                         self.config.advice,
@@ -113,15 +112,35 @@ impl<F: FieldExt> FibonacciChip<F> {
     }
 }
 
-#[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+/// Starting from `f(0)` and `f(1)` at instance rows 0 and 1, proves that
+/// `f(nrows - 1)` is the value at instance row 2.
+pub struct MyCircuit<F> {
+    pub nrows: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F> MyCircuit<F> {
+    pub fn new(nrows: usize) -> Self {
+        assert!(nrows >= 3);
+        Self {
+            nrows,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F> Default for MyCircuit<F> {
+    fn default() -> Self {
+        Self::new(10)
+    }
+}
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FibonacciConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self::new(self.nrows)
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -137,7 +156,7 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     ) -> Result<(), Error> {
         let chip = FibonacciChip::construct(config);
 
-        let out_cell = chip.assign(layouter.namespace(|| "entire table"), 10)?;
+        let out_cell = chip.assign(layouter.namespace(|| "entire table"), self.nrows)?;
 
         chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
 
@@ -148,8 +167,8 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::MyCircuit;
-    use std::marker::PhantomData;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use proptest::prelude::*;
 
     #[test]
     fn fibonacci_example2() {
@@ -159,7 +178,7 @@ mod tests {
         let b = Fp::from(1); // F[1]
         let out = Fp::from(55); // F[9]
 
-        let circuit = MyCircuit(PhantomData);
+        let circuit = MyCircuit::new(10);
 
         let mut public_input = vec![a, b, out];
 
@@ -172,6 +191,80 @@ mod tests {
         // _prover.assert_satisfied();
     }
 
+    #[test]
+    fn fibonacci_example2_three_rows() {
+        // The shortest table has a single gate, on row 0.
+        let circuit = MyCircuit::new(3);
+
+        let public_input = vec![Fp::from(2), Fp::from(3), Fp::from(5)];
+        let prover = MockProver::run(4, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+
+        let public_input = vec![Fp::from(2), Fp::from(3), Fp::from(6)];
+        let prover = MockProver::run(4, &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    /// Native reference: the `n`-th term of the sequence starting at `f0, f1`.
+    fn fibonacci(f0: u64, f1: u64, n: usize) -> u64 {
+        let (mut a, mut b) = (f0, f1);
+        for _ in 0..n {
+            (a, b) = (b, a + b);
+        }
+        a
+    }
+
+    proptest! {
+        #[test]
+        fn fibonacci_example2_matches_reference(
+            f0 in 0u64..1 << 16,
+            f1 in 0u64..1 << 16,
+            nrows in 3usize..=20,
+        ) {
+            let circuit = MyCircuit::<Fp>::new(nrows);
+            let out = Fp::from(fibonacci(f0, f1, nrows - 1));
+
+            let public_input = vec![Fp::from(f0), Fp::from(f1), out];
+            let prover = MockProver::run(5, &circuit, vec![public_input]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            let public_input = vec![Fp::from(f0), Fp::from(f1), out + Fp::one()];
+            let prover = MockProver::run(5, &circuit, vec![public_input]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn regression_fibonacci_example2() {
+        let circuit = MyCircuit::<Fp>::new(10);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+
+        crate::regression::assert_vk_fingerprint("fibonacci_example2", 4, &circuit);
+        crate::regression::assert_golden_proof(
+            "fibonacci_example2",
+            4,
+            &circuit,
+            &[public_input],
+        );
+    }
+
+    #[test]
+    fn under_constrained_fibonacci_example2() {
+        let circuit = MyCircuit::<Fp>::new(10);
+        let report = crate::analysis::under_constrained::analyze(4, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_fibonacci_example2() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::<Fp>::new(10);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+        let report = fuzz(4, &circuit, vec![public_input], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_fibo2() {
@@ -180,7 +273,7 @@ mod tests {
         root.fill(&WHITE).unwrap();
         let root = root.titled("Fib 2 Layout", ("sans-serif", 60)).unwrap();
 
-        let circuit = MyCircuit::<Fp>(PhantomData);
+        let circuit = MyCircuit::<Fp>::new(10);
         halo2_proofs::dev::CircuitLayout::default()
             .render(4, &circuit, &root)
             .unwrap();
@@ -7,7 +7,7 @@ use halo2_proofs::{
 };
 
 #[derive(Debug, Clone)]
-struct FunctionConfig<F: FieldExt> {
+pub struct FunctionConfig<F: FieldExt> {
     selector: Selector,
     a: Column<Advice>,
     b: Column<Advice>,
@@ -17,7 +17,7 @@ struct FunctionConfig<F: FieldExt> {
 }
 
 #[derive(Debug, Clone)]
-struct FunctionChip<F: FieldExt> {
+pub struct FunctionChip<F: FieldExt> {
     config: FunctionConfig<F>,
 }
 
@@ -88,11 +88,12 @@ impl<F: FieldExt> FunctionChip<F> {
     }
 }
 
+/// Computes `c` if `a == b` and `a - b` otherwise.
 #[derive(Default)]
-struct FunctionCircuit<F> {
-    a: F,
-    b: F,
-    c: F,
+pub struct FunctionCircuit<F> {
+    pub a: F,
+    pub b: F,
+    pub c: F,
 }
 
 impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
@@ -117,7 +118,12 @@ impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use halo2_proofs::{
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Column, Instance},
+    };
+    use proptest::prelude::*;
 
     #[test]
     fn test_example3() {
@@ -130,4 +136,111 @@ mod tests {
         let prover = MockProver::run(4, &circuit, vec![]).unwrap();
         prover.assert_satisfied();
     }
+
+    /// `FunctionCircuit` with its output constrained to the first instance row, so
+    /// that tests can check the value the chip computes.
+    #[derive(Default)]
+    struct PublicOutputCircuit(FunctionCircuit<Fp>);
+
+    impl Circuit<Fp> for PublicOutputCircuit {
+        type Config = (FunctionConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let config = FunctionChip::configure(meta);
+            let instance = meta.instance_column();
+            meta.enable_equality(config.output);
+            meta.enable_equality(instance);
+            (config, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let chip = FunctionChip::construct(config);
+            let output = chip.assign(layouter.namespace(|| "f"), self.0.a, self.0.b, self.0.c)?;
+            layouter.constrain_instance(output.cell(), instance, 0)
+        }
+    }
+
+    /// Native reference for `FunctionChip`.
+    fn function(a: Fp, b: Fp, c: Fp) -> Fp {
+        if a == b {
+            c
+        } else {
+            a - b
+        }
+    }
+
+    /// Inputs with `a == b` half of the time, which uniform sampling would never hit.
+    fn inputs() -> impl Strategy<Value = (u64, u64, u64)> {
+        prop_oneof![
+            (any::<u64>(), any::<u64>()).prop_map(|(a, c)| (a, a, c)),
+            (any::<u64>(), any::<u64>(), any::<u64>()),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn function_matches_reference((a, b, c) in inputs()) {
+            let (a, b, c) = (Fp::from(a), Fp::from(b), Fp::from(c));
+            let circuit = PublicOutputCircuit(FunctionCircuit { a, b, c });
+            let out = function(a, b, c);
+
+            let prover = MockProver::run(4, &circuit, vec![vec![out]]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            let prover = MockProver::run(4, &circuit, vec![vec![out + Fp::one()]]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn regression_fibonacci_example3() {
+        let circuit = FunctionCircuit {
+            a: Fp::from(10),
+            b: Fp::from(12),
+            c: Fp::from(15),
+        };
+
+        crate::regression::assert_vk_fingerprint("fibonacci_example3", 4, &circuit);
+        crate::regression::assert_golden_proof("fibonacci_example3", 4, &circuit, &[]);
+    }
+
+    #[test]
+    fn under_constrained_fibonacci_example3() {
+        let circuit = FunctionCircuit {
+            a: Fp::from(10),
+            b: Fp::from(12),
+            c: Fp::from(15),
+        };
+        let report = crate::analysis::under_constrained::analyze(4, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_fibonacci_example3() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = FunctionCircuit {
+            a: Fp::from(10),
+            b: Fp::from(12),
+            c: Fp::from(15),
+        };
+        let report = fuzz(4, &circuit, vec![], Mutations::Pairs).unwrap();
+
+        // With `a != b` the output ignores `c`, and nothing is public, so shifting `a`
+        // and `b` together keeps `a - b`.
+        assert_eq!(report.free_cells.len(), 1, "{}", report);
+        assert_eq!(report.free_cells[0].annotation, "c");
+        assert_eq!(report.free_pairs.len(), 1, "{}", report);
+        let (a, b) = &report.free_pairs[0];
+        assert_eq!((a.annotation.as_str(), b.annotation.as_str()), ("a", "b"));
+    }
 }
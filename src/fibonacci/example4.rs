@@ -1,8 +1,10 @@
 use std::marker::PhantomData;
 use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
 
+use crate::tables::{TableRegistry, TableSpec};
+
 #[derive(Debug, Clone)]
-struct FibonacciConfig {
+pub struct FibonacciConfig {
     pub advice: [Column<Advice>; 3],
     pub s_add: Selector,
     pub s_xor: Selector,
@@ -11,7 +13,7 @@ struct FibonacciConfig {
 }
 
 #[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
+pub struct FibonacciChip<F: FieldExt> {
     config: FibonacciConfig,
     _marker: PhantomData<F>,
 }
@@ -24,7 +26,11 @@ impl<F: FieldExt> FibonacciChip<F> {
         }
     }
 
-    pub fn configure(meta: &mut ConstraintSystem<F>) -> FibonacciConfig {
+    /// Requests the xor table from `tables`, which loads it.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        tables: &mut TableRegistry<F>,
+    ) -> FibonacciConfig {
         let col_a = meta.advice_column();
         let col_b = meta.advice_column();
         let col_c = meta.advice_column();
@@ -32,11 +38,10 @@ impl<F: FieldExt> FibonacciChip<F> {
         let s_xor = meta.complex_selector();
         let instance = meta.instance_column();
 
-        let xor_table = [
-            meta.lookup_table_column(),
-            meta.lookup_table_column(),
-            meta.lookup_table_column(),
-        ];
+        let xor_table: [TableColumn; 3] = tables
+            .request(meta, TableSpec::Xor { bits: 5 })
+            .try_into()
+            .unwrap();
 
         meta.enable_equality(col_a);
         meta.enable_equality(col_b);
@@ -76,42 +81,6 @@ impl<F: FieldExt> FibonacciChip<F> {
         }
     }
 
-    fn load_table(
-        &self,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        layouter.assign_table(
-            || "xor_table",
-            |mut table| {
-                let mut idx = 0;
-                for lhs in 0..32 {
-                    for rhs in 0..32 {
-                        table.assign_cell(
-                            || "lhs",
-                            self.config.xor_table[0],
-                            idx,
-                            || Value::known(F::from(lhs)),
-                        )?;
-                        table.assign_cell(
-                            || "rhs",
-                            self.config.xor_table[1],
-                            idx,
-                            || Value::known(F::from(rhs)),
-                        )?;
-                        table.assign_cell(
-                            || "lhs ^ rhs",
-                            self.config.xor_table[2],
-                            idx,
-                            || Value::known(F::from(lhs ^ rhs)),
-                        )?;
-                        idx += 1;
-                    }
-                }
-                Ok(())
-            }
-        )
-    }
-
     #[allow(clippy::type_complexity)]
     pub fn assign(
         &self,
@@ -201,11 +170,13 @@ impl<F: FieldExt> FibonacciChip<F> {
     }
 }
 
+/// Starting from `f(0)` and `f(1)` at instance rows 0 and 1, alternates additions
+/// and 5-bit xors over 8 rows and exposes the last value at instance row 2.
 #[derive(Default)]
-struct MyCircuit<F>(PhantomData<F>);
+pub struct MyCircuit<F>(PhantomData<F>);
 
 impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-    type Config = FibonacciConfig;
+    type Config = (FibonacciConfig, TableRegistry<F>);
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
@@ -213,16 +184,18 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        FibonacciChip::configure(meta)
+        let mut tables = TableRegistry::new();
+        let config = FibonacciChip::configure(meta, &mut tables);
+        (config, tables)
     }
 
     fn synthesize(
         &self,
-        config: Self::Config,
+        (config, tables): Self::Config,
         mut layouter: impl Layouter<F>,
     ) -> Result<(), Error> {
+        tables.load(&mut layouter.namespace(|| "lookup table"))?;
         let chip = FibonacciChip::construct(config);
-        chip.load_table(layouter.namespace(|| "lookup table"))?;
         let out_cell = chip.assign(layouter.namespace(|| "entire table"), 8)?;
         chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
 
@@ -232,9 +205,10 @@ impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
 
 #[cfg(test)]
 mod tests {
-    use super::MyCircuit;
-    use std::marker::PhantomData;
+    use super::*;
     use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use crate::{range_check::example2::RangeCheckConfig, xor::XorConfig};
+    use proptest::prelude::*;
 
     #[test]
     fn fibonacci_example4() {
@@ -257,6 +231,145 @@ mod tests {
         // _prover.assert_satisfied();
     }
 
+    /// Native reference for `MyCircuit`: each row shifts `(a, b)` to the previous
+    /// `(b, c)`, and `c` alternates between `a + b` on even rows and `a ^ b` on odd
+    /// rows. Returns `None` when an xor operand does not fit the 5-bit table.
+    fn reference(f0: u64, f1: u64, nrows: usize) -> Option<u64> {
+        let (mut b, mut c) = (f1, f0 + f1);
+        for row in 1..nrows {
+            let a = b;
+            b = c;
+            c = if row % 2 == 0 {
+                a + b
+            } else if a < 32 && b < 32 {
+                a ^ b
+            } else {
+                return None;
+            };
+        }
+        Some(c)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn fibonacci_example4_matches_reference(f0 in 0u64..64, f1 in 0u64..64) {
+            let circuit = MyCircuit::<Fp>(PhantomData);
+
+            match reference(f0, f1, 8) {
+                Some(out) => {
+                    let out = Fp::from(out);
+                    let public_input = vec![Fp::from(f0), Fp::from(f1), out];
+                    let prover = MockProver::run(11, &circuit, vec![public_input]).unwrap();
+                    prop_assert_eq!(prover.verify(), Ok(()));
+
+                    let public_input = vec![Fp::from(f0), Fp::from(f1), out + Fp::one()];
+                    let prover = MockProver::run(11, &circuit, vec![public_input]).unwrap();
+                    prop_assert!(prover.verify().is_err());
+                }
+                None => {
+                    let public_input = vec![Fp::from(f0), Fp::from(f1), Fp::zero()];
+                    let prover = MockProver::run(11, &circuit, vec![public_input]).unwrap();
+                    prop_assert!(prover.verify().is_err());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn regression_fibonacci_example4() {
+        let circuit = MyCircuit::<Fp>(PhantomData);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(21)];
+
+        crate::regression::assert_vk_fingerprint("fibonacci_example4", 11, &circuit);
+        crate::regression::assert_golden_proof(
+            "fibonacci_example4",
+            11,
+            &circuit,
+            &[public_input],
+        );
+    }
+
+    /// `MyCircuit` next to a lookup range check of its output, both configured
+    /// through one registry.
+    #[derive(Default)]
+    struct SharedTablesCircuit;
+
+    impl Circuit<Fp> for SharedTablesCircuit {
+        type Config = (
+            FibonacciConfig,
+            RangeCheckConfig<Fp, 8, 32>,
+            XorConfig<Fp, 5>,
+            TableRegistry<Fp>,
+        );
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let mut tables = TableRegistry::new();
+            let fibonacci = FibonacciChip::configure(meta, &mut tables);
+            let value = meta.advice_column();
+            let range_check = RangeCheckConfig::configure_with_registry(meta, value, &mut tables);
+            let xor = XorConfig::configure(meta, fibonacci.advice, &mut tables);
+            (fibonacci, range_check, xor, tables)
+        }
+
+        fn synthesize(
+            &self,
+            (fibonacci, range_check, _, tables): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            tables.load(&mut layouter.namespace(|| "lookup tables"))?;
+            let chip = FibonacciChip::construct(fibonacci);
+            let out_cell = chip.assign(layouter.namespace(|| "entire table"), 8)?;
+            range_check.assign_lookup(
+                layouter.namespace(|| "range check"),
+                out_cell.value().map(|out| (*out).into()),
+            )?;
+            chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)
+        }
+    }
+
+    #[test]
+    fn fibonacci_example4_shares_tables() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let (fibonacci, range_check, xor, tables) = SharedTablesCircuit::configure(&mut meta);
+        let names: Vec<_> = tables.specs().map(TableSpec::name).collect();
+        assert_eq!(names, ["xor of 5 bits", "range 0..32"]);
+        assert_eq!(fibonacci.xor_table, xor.table());
+        assert_eq!(tables.get("range 0..32"), Some(&[range_check.table.value][..]));
+
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(21)];
+        MockProver::run(11, &SharedTablesCircuit, vec![public_input])
+            .unwrap()
+            .assert_satisfied();
+
+        // The two tables, the Fibonacci region and the range check.
+        let layout = crate::analysis::layout::measure(&SharedTablesCircuit).unwrap();
+        assert_eq!(layout.regions, 4, "{}", layout);
+    }
+
+    #[test]
+    fn under_constrained_fibonacci_example4() {
+        let circuit = MyCircuit::<Fp>(PhantomData);
+        let report = crate::analysis::under_constrained::analyze(11, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_fibonacci_example4() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::<Fp>(PhantomData);
+        let public_input = vec![Fp::from(1), Fp::from(1), Fp::from(21)];
+        let report = fuzz(11, &circuit, vec![public_input], Mutations::Single).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_fibonacci1() {
@@ -0,0 +1,430 @@
+use crate::boolean::AssignedBit;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+/// Returns the coefficients `r_i` for which `r_0 * values[0] + r_1 * values[1] + ..`
+/// is 1: the inverse of the first nonzero value and 0 for the others, or all 0 if
+/// every value is zero.
+fn coefficients<F: FieldExt>(values: &[F]) -> Vec<F> {
+    let first = values.iter().position(|value| *value != F::zero());
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| match first {
+            Some(first) if first == i => value.invert().unwrap(),
+            _ => F::zero(),
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct IsAllZeroConfig<F, const N: usize> {
+    pub coefficient: Column<Advice>,
+    pub is_all_zero_expr: Expression<F>,
+}
+
+impl<F: FieldExt, const N: usize> IsAllZeroConfig<F, N> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_all_zero_expr.clone()
+    }
+
+    pub fn any_nonzero_expr(&self) -> Expression<F> {
+        Expression::Constant(F::one()) - self.is_all_zero_expr.clone()
+    }
+}
+
+/// Checks whether `N` values are all zero with a single inverse, where `IsZeroChip`
+/// would need one per value.
+///
+/// Each value `v_i` gets a witnessed coefficient `r_i`, read from the `coefficient`
+/// column at rows `offset..offset + N`, and `expr() = 1 - (r_0 * v_0 + r_1 * v_1 + ..)`.
+/// The gate requires `expr() * v_i = 0` for every value, so `expr()` is 1 if they
+/// are all zero and 0 otherwise, whatever coefficients the prover picks. `assign`
+/// sets the coefficient of the first nonzero value to its inverse and the others to
+/// 0. The gate has degree 4 for any `N`.
+pub struct IsAllZeroChip<F: FieldExt, const N: usize> {
+    config: IsAllZeroConfig<F, N>,
+}
+
+impl<F: FieldExt, const N: usize> IsAllZeroChip<F, N> {
+    pub fn construct(config: IsAllZeroConfig<F, N>) -> Self {
+        IsAllZeroChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        values: impl FnOnce(&mut VirtualCells<'_, F>) -> [Expression<F>; N],
+        coefficient: Column<Advice>,
+    ) -> IsAllZeroConfig<F, N> {
+        let mut is_all_zero_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("is_all_zero", |meta| {
+            let values = values(meta);
+            let q_enable = q_enable(meta);
+
+            let combination = values.iter().enumerate().fold(
+                Expression::Constant(F::zero()),
+                |acc, (row, value)| {
+                    acc + meta.query_advice(coefficient, Rotation(row as i32)) * value.clone()
+                },
+            );
+            is_all_zero_expr = Expression::Constant(F::one()) - combination;
+
+            values
+                .into_iter()
+                .map(|value| q_enable.clone() * value * is_all_zero_expr.clone())
+                .collect::<Vec<_>>()
+        });
+
+        IsAllZeroConfig {
+            coefficient,
+            is_all_zero_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        values: [Value<F>; N],
+    ) -> Result<(), Error> {
+        let coefficients = values
+            .into_iter()
+            .collect::<Value<Vec<F>>>()
+            .map(|values| coefficients(&values));
+        for row in 0..N {
+            region.assign_advice(
+                || "coefficient",
+                self.config.coefficient,
+                offset + row,
+                || coefficients.as_ref().map(|coefficients| coefficients[row]),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AllZeroConfig<F, const N: usize> {
+    pub selector: Selector,
+    pub value: Column<Advice>,
+    pub all_zero: Column<Advice>,
+    pub any_nonzero: Column<Advice>,
+    pub is_all_zero: IsAllZeroConfig<F, N>,
+}
+
+/// Writes whether `N` assigned values are all zero, and its negation, into advice
+/// cells that can be copied into other regions.
+///
+///  value | coefficient | all_zero | any_nonzero | selector
+/// -------+-------------+----------+-------------+---------
+///   v_0  |     r_0     |    z     |    1 - z    |    1
+///   v_1  |     r_1     |          |             |    0
+///   ..   |     ..      |          |             |    0
+///
+pub struct AllZeroChip<F: FieldExt, const N: usize> {
+    config: AllZeroConfig<F, N>,
+}
+
+impl<F: FieldExt, const N: usize> AllZeroChip<F, N> {
+    pub fn construct(config: AllZeroConfig<F, N>) -> Self {
+        AllZeroChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        coefficient: Column<Advice>,
+        all_zero: Column<Advice>,
+        any_nonzero: Column<Advice>,
+    ) -> AllZeroConfig<F, N> {
+        let selector = meta.selector();
+
+        meta.enable_equality(value);
+        meta.enable_equality(all_zero);
+        meta.enable_equality(any_nonzero);
+
+        let is_all_zero = IsAllZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| std::array::from_fn(|row| meta.query_advice(value, Rotation(row as i32))),
+            coefficient,
+        );
+
+        meta.create_gate("all_zero", |meta| {
+            let s = meta.query_selector(selector);
+            let all_zero = meta.query_advice(all_zero, Rotation::cur());
+            let any_nonzero = meta.query_advice(any_nonzero, Rotation::cur());
+            vec![
+                s.clone() * (all_zero - is_all_zero.expr()),
+                s * (any_nonzero - is_all_zero.any_nonzero_expr()),
+            ]
+        });
+
+        AllZeroConfig {
+            selector,
+            value,
+            all_zero,
+            any_nonzero,
+            is_all_zero,
+        }
+    }
+
+    /// Returns a cell holding 1 if all `values` are zero and 0 otherwise.
+    pub fn is_all_zero(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>; N],
+    ) -> Result<AssignedBit<F>, Error> {
+        self.assign(layouter, values).map(|(all_zero, _)| all_zero)
+    }
+
+    /// Returns a cell holding 1 if any of `values` is nonzero and 0 otherwise.
+    pub fn is_any_nonzero(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>; N],
+    ) -> Result<AssignedBit<F>, Error> {
+        self.assign(layouter, values)
+            .map(|(_, any_nonzero)| any_nonzero)
+    }
+
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        values: &[AssignedCell<F, F>; N],
+    ) -> Result<(AssignedBit<F>, AssignedBit<F>), Error> {
+        let is_all_zero_chip = IsAllZeroChip::construct(self.config.is_all_zero.clone());
+
+        layouter.assign_region(
+            || "all zero",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                for (row, value) in values.iter().enumerate() {
+                    value.copy_advice(|| "value", &mut region, self.config.value, row)?;
+                }
+
+                let values = values.clone().map(|value| value.value().copied());
+                is_all_zero_chip.assign(&mut region, 0, values)?;
+
+                let all_zero = values
+                    .into_iter()
+                    .collect::<Value<Vec<F>>>()
+                    .map(|values| values.iter().all(|value| *value == F::zero()));
+                let all_zero_cell = region.assign_advice(
+                    || "all zero",
+                    self.config.all_zero,
+                    0,
+                    || all_zero.map(|all_zero| F::from(all_zero as u64)),
+                )?;
+                let any_nonzero_cell = region.assign_advice(
+                    || "any nonzero",
+                    self.config.any_nonzero,
+                    0,
+                    || all_zero.map(|all_zero| F::from(!all_zero as u64)),
+                )?;
+                Ok((
+                    AssignedBit::new(all_zero_cell),
+                    AssignedBit::new(any_nonzero_cell),
+                ))
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    const N: usize = 4;
+
+    /// Reads `N` values from the first instance rows, and exposes whether they are
+    /// all zero and whether any is nonzero at the two rows after them.
+    #[derive(Default)]
+    struct MyCircuit {
+        /// Skips the chip and assigns `all_zero = 1` with zero coefficients, as a
+        /// malicious prover could.
+        claim_all_zero: bool,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (AllZeroConfig<Fp, N>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                claim_all_zero: self.claim_all_zero,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let [value, coefficient, all_zero, any_nonzero] = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                AllZeroChip::configure(meta, value, coefficient, all_zero, any_nonzero),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            if self.claim_all_zero {
+                return layouter.assign_region(
+                    || "all zero",
+                    |mut region| {
+                        config.selector.enable(&mut region, 0)?;
+                        for row in 0..N {
+                            region.assign_advice_from_instance(
+                                || "value",
+                                instance,
+                                row,
+                                config.value,
+                                row,
+                            )?;
+                        }
+                        for row in 0..N {
+                            region.assign_advice(
+                                || "coefficient",
+                                config.is_all_zero.coefficient,
+                                row,
+                                || Value::known(Fp::zero()),
+                            )?;
+                        }
+                        region.assign_advice(
+                            || "all zero",
+                            config.all_zero,
+                            0,
+                            || Value::known(Fp::one()),
+                        )?;
+                        region.assign_advice(
+                            || "any nonzero",
+                            config.any_nonzero,
+                            0,
+                            || Value::known(Fp::zero()),
+                        )?;
+                        Ok(())
+                    },
+                );
+            }
+
+            let values = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let mut values = vec![];
+                    for row in 0..N {
+                        values.push(region.assign_advice_from_instance(
+                            || "value",
+                            instance,
+                            row,
+                            config.value,
+                            row,
+                        )?);
+                    }
+                    Ok(values)
+                },
+            )?;
+            let values: [_; N] = values.try_into().unwrap();
+
+            let chip = AllZeroChip::construct(config);
+            let all_zero = chip.is_all_zero(layouter.namespace(|| "all zero"), &values)?;
+            let any_nonzero = chip.is_any_nonzero(layouter.namespace(|| "any nonzero"), &values)?;
+            layouter.constrain_instance(all_zero.cell(), instance, N)?;
+            layouter.constrain_instance(any_nonzero.cell(), instance, N + 1)
+        }
+    }
+
+    fn instance(values: [Fp; N], all_zero: bool) -> Vec<Fp> {
+        let mut instance = values.to_vec();
+        instance.push(Fp::from(all_zero as u64));
+        instance.push(Fp::from(!all_zero as u64));
+        instance
+    }
+
+    #[test]
+    fn all_zero() {
+        let zeros = [Fp::zero(); N];
+        let prover = MockProver::run(5, &MyCircuit::default(), vec![instance(zeros, true)]);
+        prover.unwrap().assert_satisfied();
+
+        let prover = MockProver::run(5, &MyCircuit::default(), vec![instance(zeros, false)]);
+        assert!(prover.unwrap().verify().is_err());
+    }
+
+    #[test]
+    fn any_nonzero() {
+        let mut values = [Fp::zero(); N];
+        for i in 0..N {
+            values[i] = Fp::from(i as u64 + 1);
+            let prover = MockProver::run(5, &MyCircuit::default(), vec![instance(values, false)]);
+            prover.unwrap().assert_satisfied();
+
+            let prover = MockProver::run(5, &MyCircuit::default(), vec![instance(values, true)]);
+            assert!(prover.unwrap().verify().is_err());
+        }
+    }
+
+    #[test]
+    fn all_zero_cancelling_values() {
+        // Nonzero values that add up to 0.
+        let values = [Fp::from(6), -Fp::from(11), Fp::from(6), -Fp::one()];
+        assert_eq!(
+            values.iter().fold(Fp::zero(), |acc, value| acc + value),
+            Fp::zero()
+        );
+
+        let prover = MockProver::run(5, &MyCircuit::default(), vec![instance(values, false)]);
+        prover.unwrap().assert_satisfied();
+
+        let circuit = MyCircuit {
+            claim_all_zero: true,
+        };
+        let prover = MockProver::run(5, &circuit, vec![instance(values, true)]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    fn degree<const N: usize>() -> usize {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let [value, coefficient, all_zero, any_nonzero] = [(); 4].map(|_| meta.advice_column());
+        AllZeroChip::<Fp, N>::configure(&mut meta, value, coefficient, all_zero, any_nonzero);
+        meta.degree()
+    }
+
+    #[test]
+    fn degree_does_not_grow_with_n() {
+        assert_eq!(degree::<1>(), 4);
+        assert_eq!(degree::<8>(), 4);
+    }
+
+    #[test]
+    fn mutation_all_zero() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let values = [Fp::zero(), Fp::from(3), Fp::zero(), Fp::from(5)];
+        let report = fuzz(
+            5,
+            &MyCircuit::default(),
+            vec![instance(values, false)],
+            Mutations::Pairs,
+        )
+        .unwrap();
+        // The coefficients of the zero values multiply zero, so they are free in both
+        // regions, and do not change whether the values are all zero.
+        let free: Vec<_> = report
+            .free_cells
+            .iter()
+            .map(|cell| (cell.annotation.as_str(), cell.row))
+            .collect();
+        assert_eq!(
+            free,
+            [4, 6, 8, 10].map(|row| ("coefficient", row)),
+            "{}",
+            report
+        );
+        assert!(report.free_pairs.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,203 @@
+use crate::{
+    boolean::AssignedBit,
+    is_zero::{IsZeroChip, IsZeroConfig},
+};
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F> {
+    pub selector: Selector,
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub output: Column<Advice>,
+    pub a_equals_b: IsZeroConfig<F>,
+}
+
+/// Writes `a == b` into an advice cell, so that the result can be copied into other
+/// regions instead of being inlined into a gate like `IsZeroConfig::expr`.
+pub struct IsEqualChip<F: FieldExt> {
+    config: IsEqualConfig<F>,
+}
+
+impl<F: FieldExt> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        IsEqualChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let selector = meta.selector();
+
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(output);
+
+        let a_equals_b = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| meta.query_advice(a, Rotation::cur()) - meta.query_advice(b, Rotation::cur()),
+            value_inv,
+        );
+
+        meta.create_gate("is_equal", |meta| {
+            //
+            // a | b | value_inv | output | selector
+            // --+---+-----------+--------+---------
+            // a | b | 1/(a - b) | a == b |    s
+            //
+            // The "is_zero" gate pins `a_equals_b.expr()` to 1 if a == b and to 0
+            // otherwise, so the output is boolean without a gate of its own.
+            //
+            let s = meta.query_selector(selector);
+            let output = meta.query_advice(output, Rotation::cur());
+            vec![s * (output - a_equals_b.expr())]
+        });
+
+        IsEqualConfig {
+            selector,
+            a,
+            b,
+            output,
+            a_equals_b,
+        }
+    }
+
+    /// Copies `a` and `b` into a new region and returns the cell holding 1 if they
+    /// are equal and 0 otherwise.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        let is_zero_chip = IsZeroChip::construct(self.config.a_equals_b.clone());
+
+        layouter.assign_region(
+            || "a == b",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, self.config.a, 0)?;
+                b.copy_advice(|| "b", &mut region, self.config.b, 0)?;
+
+                let difference = a.value().copied() - b.value();
+                is_zero_chip.assign(&mut region, 0, difference)?;
+
+                let output = difference.map(|difference| {
+                    if difference == F::zero() {
+                        F::one()
+                    } else {
+                        F::zero()
+                    }
+                });
+                region
+                    .assign_advice(|| "a == b", self.config.output, 0, || output)
+                    .map(AssignedBit::new)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    /// Reads `a` and `b` from instance rows 0 and 1, and exposes `a == b` at
+    /// instance row 2 after copying it through another region.
+    #[derive(Default)]
+    struct IsEqualCircuit;
+
+    impl Circuit<Fp> for IsEqualCircuit {
+        type Config = (IsEqualConfig<Fp>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let a = meta.advice_column();
+            let b = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (
+                IsEqualChip::configure(meta, a, b, value_inv, output),
+                instance,
+            )
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let (a, b) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let a = region.assign_advice_from_instance(|| "a", instance, 0, config.a, 0)?;
+                    let b = region.assign_advice_from_instance(|| "b", instance, 1, config.b, 0)?;
+                    Ok((a, b))
+                },
+            )?;
+
+            let chip = IsEqualChip::construct(config.clone());
+            let output = chip.assign(layouter.namespace(|| "is_equal"), &a, &b)?;
+
+            let copied = layouter.assign_region(
+                || "copy output",
+                |mut region| output.copy_advice(|| "a == b", &mut region, config.a, 0),
+            )?;
+            layouter.constrain_instance(copied.cell(), instance, 2)
+        }
+    }
+
+    fn verify(a: u64, b: u64, output: u64) -> Result<(), Vec<halo2_proofs::dev::VerifyFailure>> {
+        let instance = vec![Fp::from(a), Fp::from(b), Fp::from(output)];
+        MockProver::run(4, &IsEqualCircuit, vec![instance])
+            .unwrap()
+            .verify()
+    }
+
+    #[test]
+    fn is_equal() {
+        assert_eq!(verify(7, 7, 1), Ok(()));
+        assert_eq!(verify(0, 0, 1), Ok(()));
+        assert_eq!(verify(7, 8, 0), Ok(()));
+        assert_eq!(verify(0, 8, 0), Ok(()));
+    }
+
+    #[test]
+    fn is_equal_rejects_wrong_output() {
+        assert!(verify(7, 7, 0).is_err());
+        assert!(verify(7, 8, 1).is_err());
+        assert!(verify(7, 8, 2).is_err());
+    }
+
+    #[test]
+    fn mutation_is_equal() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let instance = vec![Fp::from(7), Fp::from(8), Fp::zero()];
+        let report = fuzz(4, &IsEqualCircuit, vec![instance], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+
+        // With a == b the "is_zero" gate accepts any inverse, but the output stays
+        // pinned.
+        let instance = vec![Fp::from(7), Fp::from(7), Fp::one()];
+        let report = fuzz(4, &IsEqualCircuit, vec![instance], Mutations::Pairs).unwrap();
+        let free: Vec<_> = report
+            .free_cells
+            .iter()
+            .map(|cell| cell.annotation.as_str())
+            .collect();
+        assert_eq!(free, vec!["value inv"], "{}", report);
+        assert!(report.free_pairs.is_empty(), "{}", report);
+    }
+}
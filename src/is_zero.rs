@@ -63,3 +63,178 @@ impl<F: FieldExt> IsZeroChip<F> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        arithmetic::Field,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+    use proptest::prelude::*;
+
+    use super::*;
+
+    /// Lays out one row with `value`, its `value_inv` and `is_zero`, which the
+    /// "is_zero output" gate sets to `1 - value * value_inv`.
+    struct MyCircuit {
+        value: Value<Fp>,
+        /// Witnesses this `value_inv` instead of the one `IsZeroChip` computes.
+        value_inv: Option<Fp>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (Selector, [Column<Advice>; 2], IsZeroConfig<Fp>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                value_inv: self.value_inv,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let q_enable = meta.selector();
+            let [value, value_inv, is_zero] = [(); 3].map(|_| meta.advice_column());
+
+            let config = IsZeroChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                value_inv,
+            );
+
+            meta.create_gate("is_zero output", |meta| {
+                let s = meta.query_selector(q_enable);
+                let is_zero = meta.query_advice(is_zero, Rotation::cur());
+                vec![s * (is_zero - config.expr())]
+            });
+
+            (q_enable, [value, is_zero], config)
+        }
+
+        fn synthesize(
+            &self,
+            (q_enable, [value, is_zero], config): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            let value_inv_column = config.value_inv;
+            let chip = IsZeroChip::construct(config);
+
+            layouter.assign_region(
+                || "is_zero",
+                |mut region| {
+                    q_enable.enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", value, 0, || self.value)?;
+
+                    let value_inv = match self.value_inv {
+                        None => {
+                            chip.assign(&mut region, 0, self.value)?;
+                            self.value.map(|value| value.invert().unwrap_or(Fp::zero()))
+                        }
+                        Some(value_inv) => {
+                            let value_inv = Value::known(value_inv);
+                            region.assign_advice(
+                                || "value inv",
+                                value_inv_column,
+                                0,
+                                || value_inv,
+                            )?;
+                            value_inv
+                        }
+                    };
+
+                    let output = self
+                        .value
+                        .zip(value_inv)
+                        .map(|(v, inv)| Fp::one() - v * inv);
+                    region.assign_advice(|| "is_zero", is_zero, 0, || output)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    fn verify(value: Fp, value_inv: Option<Fp>) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = MyCircuit {
+            value: Value::known(value),
+            value_inv,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap().verify()
+    }
+
+    /// Asserts that the only failure is the "is_zero" gate on the row of `value`.
+    fn assert_is_zero_gate_fails(result: Result<(), Vec<VerifyFailure>>) {
+        let failures = result.unwrap_err();
+        assert_eq!(failures.len(), 1, "{:?}", failures);
+        match &failures[0] {
+            VerifyFailure::ConstraintNotSatisfied {
+                constraint,
+                location,
+                ..
+            } => {
+                assert_eq!(*constraint, ((0, "is_zero").into(), 0, "").into());
+                assert_eq!(
+                    *location,
+                    FailureLocation::InRegion {
+                        region: (0, "is_zero").into(),
+                        offset: 0,
+                    }
+                );
+            }
+            failure => panic!("unexpected failure {:?}", failure),
+        }
+    }
+
+    #[test]
+    fn is_zero_rejects_malicious_value_inv() {
+        let x = Fp::from(5);
+
+        // value = x, value_inv = 0 claims that x is zero.
+        assert_is_zero_gate_fails(verify(x, Some(Fp::zero())));
+
+        // Any other value_inv that is not 1/x leaves 1 - x * value_inv nonzero.
+        let x_inv = x.invert().unwrap();
+        for value_inv in [Fp::one(), x_inv.double(), -x_inv, x] {
+            assert_is_zero_gate_fails(verify(x, Some(value_inv)));
+        }
+
+        // The honest rows of the truth table.
+        verify(x, Some(x_inv)).unwrap();
+        verify(Fp::zero(), Some(Fp::zero())).unwrap();
+    }
+
+    #[test]
+    fn is_zero_leaves_value_inv_free_for_zero() {
+        // For value = 0, the gate holds for every value_inv and is_zero is 1.
+        for value_inv in [Fp::one(), Fp::from(7), -Fp::one()] {
+            verify(Fp::zero(), Some(value_inv)).unwrap();
+        }
+    }
+
+    /// Uniformly random field elements, and zero.
+    fn field_element() -> impl Strategy<Value = Fp> {
+        prop_oneof![
+            1 => Just(Fp::zero()),
+            9 => any::<[u8; 32]>().prop_map(|bytes| {
+                let mut wide = [0; 64];
+                wide[..32].copy_from_slice(&bytes);
+                Fp::from_bytes_wide(&wide)
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn is_zero_accepts_honest_witness(value in field_element()) {
+            prop_assert!(verify(value, None).is_ok());
+        }
+
+        #[test]
+        fn is_zero_rejects_wrong_value_inv(value in field_element(), value_inv in field_element()) {
+            prop_assume!(value != Fp::zero() && value * value_inv != Fp::one());
+            assert_is_zero_gate_fails(verify(value, Some(value_inv)));
+        }
+    }
+}
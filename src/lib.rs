@@ -1,4 +1,19 @@
-mod fibonacci;
-mod is_zero;
-mod range_check;
-mod mip;
\ No newline at end of file
+pub mod fibonacci;
+pub mod boolean;
+pub mod bits;
+pub mod is_zero;
+pub mod is_all_zero;
+pub mod is_equal;
+pub mod comparison;
+pub mod division;
+pub mod divmod;
+pub mod piecewise;
+pub mod switch;
+pub mod membership;
+pub mod tables;
+pub mod xor;
+pub mod range_check;
+pub mod mip;
+pub mod analysis;
+#[cfg(test)]
+mod regression;
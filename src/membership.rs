@@ -0,0 +1,409 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+mod set;
+pub use set::{Set, SetError};
+
+/// This helper checks that a tuple of `WIDTH` cells is one of the entries of a fixed
+/// set, such as allowed country codes or `(key, value)` pairs read from a file.
+///
+///   input_0 | .. | input_W-1 | q_lookup | table_tag | table_0 | .. | table_W-1
+///  ---------+----+-----------+----------+-----------+---------+----+-----------
+///     x_0   | .. |   x_W-1   |    1     |     0     |    0    | .. |     0
+///           |    |           |          |     1     |   s_0   | .. |   s_W-1
+///           |    |           |          |     1     |   ..    | .. |    ..
+///           |    |           |          |     0     |    0    | .. |     0
+///
+/// The set is taken at configure time, so it is part of the verifying key. The first
+/// row of the table is tagged 0, and `load` repeats it after the set to pad the table
+/// to its size. It is also what rows with the lookup disabled look up. Enabled rows
+/// look up the tag 1, so the padding is never a member, even when the set holds no
+/// all-zero tuple.
+#[derive(Debug, Clone)]
+pub struct MembershipConfig<F: FieldExt, const WIDTH: usize> {
+    q_lookup: Selector,
+    inputs: [Column<Advice>; WIDTH],
+    tag: TableColumn,
+    table: [TableColumn; WIDTH],
+    set: Set<WIDTH>,
+    size: usize,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const WIDTH: usize> MembershipConfig<F, WIDTH> {
+    /// Panics if the set and the padding row do not fit in the usable rows of a circuit
+    /// with `2^k` rows, given the blinding rows of what is configured so far. The
+    /// layouter fills the rest of the table starting from the first row after it,
+    /// which must be usable too. Chips configured afterwards can query more rotations
+    /// and leave fewer usable rows, in which case `load` fails with
+    /// `Error::NotEnoughRowsAvailable`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        inputs: [Column<Advice>; WIDTH],
+        set: Set<WIDTH>,
+        k: u32,
+    ) -> Self {
+        let size = (1usize << k).saturating_sub(meta.blinding_factors() + 1);
+        assert!(
+            set.len() + 2 <= size,
+            "a set of {} entries does not fit with its padding in the {} usable rows of k = {}",
+            set.len(),
+            size,
+            k
+        );
+
+        let q_lookup = meta.complex_selector();
+        let tag = meta.lookup_table_column();
+        let table = [(); WIDTH].map(|_| meta.lookup_table_column());
+        for input in inputs {
+            meta.enable_equality(input);
+        }
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_lookup);
+            let mut lookup = vec![(q.clone(), tag)];
+            for (input, column) in inputs.iter().zip(table) {
+                let input = meta.query_advice(*input, Rotation::cur());
+                lookup.push((q.clone() * input, column));
+            }
+            lookup
+        });
+
+        Self {
+            q_lookup,
+            inputs,
+            tag,
+            table,
+            set,
+            size,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    pub fn set(&self) -> &Set<WIDTH> {
+        &self.set
+    }
+
+    /// The rows of the table, including the padding.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Assigns the set and its padding to every row of the table but the last, which
+    /// the layouter fills. Fails with `Error::NotEnoughRowsAvailable` if the table no
+    /// longer fits in the usable rows of the configured circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load set",
+            |mut table| {
+                let padding = [0; WIDTH];
+                let entries = self.set.entries().iter().map(|entry| (1, entry));
+                let rows = std::iter::once((0, &padding))
+                    .chain(entries)
+                    .chain(std::iter::repeat((0, &padding)))
+                    .take(self.size - 1);
+                for (offset, (tag, entry)) in rows.enumerate() {
+                    table.assign_cell(|| "tag", self.tag, offset, || Value::known(F::from(tag)))?;
+                    for (column, value) in self.table.iter().zip(entry) {
+                        table.assign_cell(
+                            || "element",
+                            *column,
+                            offset,
+                            || Value::known(F::from(*value)),
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Witnesses `tuple` and checks that it is in the set.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        tuple: [Value<F>; WIDTH],
+    ) -> Result<[AssignedCell<F, F>; WIDTH], Error> {
+        layouter.assign_region(
+            || "set membership",
+            |mut region| {
+                self.q_lookup.enable(&mut region, 0)?;
+                let mut cells = vec![];
+                for (column, value) in self.inputs.iter().zip(tuple) {
+                    cells.push(region.assign_advice(|| "element", *column, 0, || value)?);
+                }
+                Ok(cells.try_into().unwrap())
+            },
+        )
+    }
+
+    /// Checks that the tuple held by `cells` is in the set.
+    pub fn copy_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cells: [&AssignedCell<F, F>; WIDTH],
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "set membership",
+            |mut region| {
+                self.q_lookup.enable(&mut region, 0)?;
+                for (column, cell) in self.inputs.iter().zip(cells) {
+                    cell.copy_advice(|| "element", &mut region, *column, 0)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+
+    const K: u32 = 5;
+
+    fn data(name: &str) -> String {
+        format!("{}/tests/data/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    /// Checks that each tuple is in the country codes of `tests/data` for WIDTH = 1,
+    /// or in its `(key, value)` pairs for WIDTH = 2, and checks the first tuple again
+    /// from its cells.
+    struct MyCircuit<const WIDTH: usize> {
+        tuples: Vec<[Value<Fp>; WIDTH]>,
+    }
+
+    impl<const WIDTH: usize> MyCircuit<WIDTH> {
+        fn new(tuples: &[[u64; WIDTH]]) -> Self {
+            Self {
+                tuples: tuples
+                    .iter()
+                    .map(|tuple| tuple.map(|value| Value::known(Fp::from(value))))
+                    .collect(),
+            }
+        }
+    }
+
+    impl<const WIDTH: usize> Circuit<Fp> for MyCircuit<WIDTH> {
+        type Config = MembershipConfig<Fp, WIDTH>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                tuples: vec![[Value::unknown(); WIDTH]; self.tuples.len()],
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let inputs = [(); WIDTH].map(|_| meta.advice_column());
+            let set = if WIDTH == 1 {
+                Set::read_csv(data("country_codes.csv"), true)
+            } else {
+                Set::read_json(data("pairs.json"))
+            };
+            let set = set.unwrap();
+            MembershipConfig::configure(meta, inputs, set, K)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+
+            let mut assigned = vec![];
+            for tuple in &self.tuples {
+                assigned.push(config.assign(layouter.namespace(|| "tuple"), *tuple)?);
+            }
+            if let Some(first) = assigned.first() {
+                let cells: Vec<_> = first.iter().collect();
+                config.copy_check(layouter.namespace(|| "copy"), cells.try_into().unwrap())?;
+            }
+            Ok(())
+        }
+    }
+
+    fn verifies<const WIDTH: usize>(tuples: &[[u64; WIDTH]]) -> bool {
+        let circuit = MyCircuit::new(tuples);
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn country_codes() {
+        let set = Set::<1>::read_csv(data("country_codes.csv"), true).unwrap();
+        assert!(verifies(set.entries()));
+
+        for code in [0, 1, 839, 841] {
+            assert!(!verifies(&[[840], [code]]), "{}", code);
+        }
+    }
+
+    #[test]
+    fn key_value_pairs() {
+        assert!(verifies(&[[1, 100], [2, 250], [3, 500]]));
+
+        // Mixed keys and values, and the all-zero padding row.
+        for pair in [[1, 250], [2, 100], [3, 100], [100, 1], [0, 0], [4, 0]] {
+            assert!(!verifies(&[pair]), "{:?}", pair);
+        }
+    }
+
+    #[test]
+    fn membership_failure() {
+        let circuit = MyCircuit::new(&[[2, 250], [2, 251]]);
+        let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (2, "set membership").into(),
+                    offset: 0
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn set_is_padded_to_usable_rows() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let inputs = [meta.advice_column()];
+        let set = Set::read_csv(data("country_codes.csv"), true).unwrap();
+        let config = MembershipConfig::configure(&mut meta, inputs, set, K);
+        assert_eq!(config.set().len(), 6);
+        assert_eq!(config.size(), (1 << K) - (meta.blinding_factors() + 1));
+    }
+
+    /// Loads the set `1..=LEN` and checks that `LEN` is in it.
+    struct FullCircuit<const LEN: u64>;
+
+    impl<const LEN: u64> Circuit<Fp> for FullCircuit<LEN> {
+        type Config = MembershipConfig<Fp, 1>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let inputs = [meta.advice_column()];
+            let set = Set::new((1..=LEN).map(|i| [i]).collect()).unwrap();
+            MembershipConfig::configure(meta, inputs, set, K)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+            config.assign(layouter.namespace(|| "last"), [Value::known(Fp::from(LEN))])?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn set_fills_usable_rows() {
+        // 26 usable rows hold the padding, 24 entries and the row the layouter fills
+        // the table from.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let config = FullCircuit::<24>::configure(&mut meta);
+        assert_eq!(config.size(), 26);
+
+        MockProver::run(K, &FullCircuit::<24>, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn set_must_fit_in_k() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        FullCircuit::<25>::configure(&mut meta);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn set_must_fit_in_small_k() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let inputs = [meta.advice_column()];
+        MembershipConfig::configure(&mut meta, inputs, Set::new(vec![[1]]).unwrap(), 2);
+    }
+
+    /// Configures a small set, then a gate that queries six rotations of another
+    /// column, which adds blinding rows.
+    struct LateQueriesCircuit;
+
+    impl Circuit<Fp> for LateQueriesCircuit {
+        type Config = MembershipConfig<Fp, 1>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let inputs = [meta.advice_column()];
+            let set = Set::new(vec![[1], [2], [3]]).unwrap();
+            let config = MembershipConfig::configure(meta, inputs, set, K);
+
+            let other = meta.advice_column();
+            meta.create_gate("rotations", |meta| {
+                (0..6)
+                    .map(|rotation| meta.query_advice(other, Rotation(rotation)))
+                    .collect::<Vec<_>>()
+            });
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)
+        }
+    }
+
+    #[test]
+    fn set_checked_again_at_load() {
+        // The table is padded to the 26 usable rows at configure time, but only 23
+        // are left afterwards.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let config = LateQueriesCircuit::configure(&mut meta);
+        assert_eq!(config.size(), 26);
+        assert_eq!((1 << K) - (meta.blinding_factors() + 1), 23);
+
+        let prover = MockProver::run(K, &LateQueriesCircuit, vec![]);
+        assert!(matches!(
+            prover,
+            Err(Error::NotEnoughRowsAvailable { current_k: K })
+        ));
+    }
+
+    #[test]
+    fn mutation_membership() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::new(&[[2, 250]]);
+        let report = fuzz(K, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Neighbouring keys and values are not in the set.
+        assert!(report.is_empty(), "{}", report);
+    }
+}
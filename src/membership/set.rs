@@ -0,0 +1,298 @@
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+
+/// Why a set could not be read.
+#[derive(Debug)]
+pub enum SetError {
+    Io(io::Error),
+    /// The file is not valid CSV or JSON. `line` counts from 1.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    /// An element of an entry is not an unsigned integer.
+    Element {
+        entry: usize,
+        message: String,
+    },
+    /// An entry does not have `expected` elements.
+    Width {
+        entry: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// An entry repeats an earlier one. Entries count from 0.
+    Duplicate {
+        entry: usize,
+        first: usize,
+    },
+    Empty,
+}
+
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::Io(err) => write!(f, "cannot read set: {}", err),
+            SetError::Parse { line, message } => write!(f, "line {}: {}", line, message),
+            SetError::Element { entry, message } => write!(f, "entry {}: {}", entry, message),
+            SetError::Width {
+                entry,
+                expected,
+                found,
+            } => write!(
+                f,
+                "entry {} has {} elements instead of {}",
+                entry, found, expected
+            ),
+            SetError::Duplicate { entry, first } => {
+                write!(f, "entry {} repeats entry {}", entry, first)
+            }
+            SetError::Empty => write!(f, "the set is empty"),
+        }
+    }
+}
+
+impl std::error::Error for SetError {}
+
+impl From<io::Error> for SetError {
+    fn from(err: io::Error) -> Self {
+        SetError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SetError {
+    fn from(err: serde_json::Error) -> Self {
+        SetError::Parse {
+            line: err.line(),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// A set of `WIDTH`-tuples of unsigned integers, in the order they were read.
+///
+/// CSV files have one tuple per line, after a header line if the caller says there is
+/// one. JSON files hold an array of tuples, where a tuple is an array of numbers, or a
+/// single number if `WIDTH` is 1. Numbers are decimal, or hexadecimal with a `0x`
+/// prefix in CSV and JSON strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Set<const WIDTH: usize> {
+    entries: Vec<[u64; WIDTH]>,
+}
+
+impl<const WIDTH: usize> Set<WIDTH> {
+    /// Fails if the set is empty or has duplicates.
+    pub fn new(entries: Vec<[u64; WIDTH]>) -> Result<Self, SetError> {
+        if entries.is_empty() {
+            return Err(SetError::Empty);
+        }
+        let mut seen = HashMap::new();
+        for (entry, tuple) in entries.iter().enumerate() {
+            if let Some(first) = seen.insert(*tuple, entry) {
+                return Err(SetError::Duplicate { entry, first });
+            }
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn read_csv(path: impl AsRef<Path>, has_header: bool) -> Result<Self, SetError> {
+        Self::from_csv(&fs::read_to_string(path)?, has_header)
+    }
+
+    pub fn read_json(path: impl AsRef<Path>) -> Result<Self, SetError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+
+    /// Skips the first non-empty line if `has_header`; every other line must be an
+    /// entry.
+    pub fn from_csv(contents: &str, has_header: bool) -> Result<Self, SetError> {
+        let lines = contents
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .skip(has_header as usize);
+
+        let mut entries = vec![];
+        for (index, line) in lines {
+            let tuple: Vec<u64> = line
+                .split(',')
+                .map(|field| parse_u64(field.trim()))
+                .collect::<Option<_>>()
+                .ok_or_else(|| SetError::Parse {
+                    line: index + 1,
+                    message: format!("`{}` is not a list of unsigned integers", line),
+                })?;
+            entries.push(Self::tuple(entries.len(), &tuple)?);
+        }
+        Self::new(entries)
+    }
+
+    pub fn from_json(contents: &str) -> Result<Self, SetError> {
+        let values: Vec<serde_json::Value> = serde_json::from_str(contents)?;
+
+        let entries = values
+            .iter()
+            .enumerate()
+            .map(|(entry, value)| {
+                let elements = match value {
+                    serde_json::Value::Array(elements) => elements.as_slice(),
+                    element => std::slice::from_ref(element),
+                };
+                let tuple = elements
+                    .iter()
+                    .map(|element| json_u64(entry, element))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Self::tuple(entry, &tuple)
+            })
+            .collect::<Result<_, _>>()?;
+        Self::new(entries)
+    }
+
+    fn tuple(entry: usize, tuple: &[u64]) -> Result<[u64; WIDTH], SetError> {
+        tuple.try_into().map_err(|_| SetError::Width {
+            entry,
+            expected: WIDTH,
+            found: tuple.len(),
+        })
+    }
+
+    pub fn entries(&self) -> &[[u64; WIDTH]] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, tuple: &[u64; WIDTH]) -> bool {
+        self.entries.contains(tuple)
+    }
+}
+
+fn parse_u64(field: &str) -> Option<u64> {
+    match field.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => field.parse().ok(),
+    }
+}
+
+/// Reads a JSON number, or a string holding a decimal or `0x` number.
+fn json_u64(entry: usize, element: &serde_json::Value) -> Result<u64, SetError> {
+    let number = match element {
+        serde_json::Value::Number(number) => number.as_u64(),
+        serde_json::Value::String(string) => parse_u64(string),
+        _ => None,
+    };
+    number.ok_or_else(|| SetError::Element {
+        entry,
+        message: format!("{} is not an unsigned integer", element),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_from_csv() {
+        let set = Set::<2>::from_csv("key, value\n1, 10\n\n2,0x14\n", true).unwrap();
+        assert_eq!(set.entries(), [[1, 10], [2, 20]]);
+
+        let set = Set::<1>::from_csv("840\n250\n", false).unwrap();
+        assert_eq!(set.entries(), [[840], [250]]);
+
+        assert!(matches!(
+            Set::<2>::from_csv("1, 10\n2\n", false),
+            Err(SetError::Width {
+                entry: 1,
+                expected: 2,
+                found: 1
+            })
+        ));
+        assert!(matches!(
+            Set::<1>::from_csv("code\n1\nfr\n", true),
+            Err(SetError::Parse { line: 3, .. })
+        ));
+        assert!(matches!(
+            Set::<1>::from_csv("code\n", true),
+            Err(SetError::Empty)
+        ));
+    }
+
+    #[test]
+    fn set_from_csv_rejects_typo_in_first_entry() {
+        assert!(matches!(
+            Set::<1>::from_csv("84O\n250\n", false),
+            Err(SetError::Parse { line: 1, .. })
+        ));
+        assert!(matches!(
+            Set::<1>::from_csv("code\n84O\n250\n", true),
+            Err(SetError::Parse { line: 2, .. })
+        ));
+
+        // Without a header, a header is an entry that does not parse.
+        assert!(matches!(
+            Set::<1>::from_csv("code\n840\n", false),
+            Err(SetError::Parse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn set_from_json() {
+        let set = Set::<2>::from_json("[[1, 10], [2, \"0x14\"]]").unwrap();
+        assert_eq!(set.entries(), [[1, 10], [2, 20]]);
+
+        let set = Set::<1>::from_json(" [840, 250,\n 4] ").unwrap();
+        assert_eq!(set.entries(), [[840], [250], [4]]);
+
+        assert!(matches!(
+            Set::<2>::from_json("[[1, 10], [2, 20, 30]]"),
+            Err(SetError::Width { entry: 1, .. })
+        ));
+        for json in ["[1, 0x2]", "{\"a\": 1}", "[1, 2] 3", "[1,\n 2"] {
+            assert!(
+                matches!(Set::<1>::from_json(json), Err(SetError::Parse { .. })),
+                "{}",
+                json
+            );
+        }
+        assert!(matches!(
+            Set::<1>::from_json("[1,\n\n x]"),
+            Err(SetError::Parse { line: 3, .. })
+        ));
+        for json in [
+            "[1, -2]",
+            "[1, 2.5]",
+            "[1, 1e2]",
+            "[1, \"two\"]",
+            "[1, null]",
+        ] {
+            assert!(
+                matches!(
+                    Set::<1>::from_json(json),
+                    Err(SetError::Element { entry: 1, .. })
+                ),
+                "{}",
+                json
+            );
+        }
+    }
+
+    #[test]
+    fn set_rejects_duplicates() {
+        assert!(matches!(
+            Set::<2>::from_csv("1, 10\n2, 20\n1, 10\n", false),
+            Err(SetError::Duplicate { entry: 2, first: 0 })
+        ));
+        assert!(matches!(
+            Set::<1>::from_json("[7, \"0x7\"]"),
+            Err(SetError::Duplicate { entry: 1, first: 0 })
+        ));
+
+        // Tuples that only share a key are distinct.
+        assert!(Set::<2>::from_csv("1, 10\n1, 20\n", false).is_ok());
+    }
+}
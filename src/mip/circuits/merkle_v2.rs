@@ -2,7 +2,7 @@ use super::super::chips::merkle_v2::{MerkleTreeV2Chip, MerkleTreeV2Config};
 use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
 
 #[derive(Default)]
-struct MerkleTreeV2Circuit<F> {
+pub struct MerkleTreeV2Circuit<F> {
     pub leaf: Value<F>,
     pub elements: Vec<Value<F>>,
     pub indices: Vec<Value<F>>,
@@ -51,16 +51,24 @@ impl<F: FieldExt> Circuit<F> for MerkleTreeV2Circuit<F> {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::MerkleTreeV2Circuit;
-    use halo2_proofs::{circuit::Value, dev::MockProver, pasta::Fp};
+    use crate::mip::chips::merkle_v2::{MerkleTreeV2Chip, MerkleTreeV2Config};
+    use halo2_proofs::{
+        circuit::{Layouter, SimpleFloorPlanner, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, ConstraintSystem, Error},
+    };
+    use proptest::prelude::*;
 
     #[test]
     #[cfg(feature = "dev-graph")]
     fn test_merkle_v2() {
         let leaf = 1u64;
-        let elements = vec![1, 1, 1, 1];
-        let indices = vec![0, 0, 0, 0];
+        let elements = [1, 1, 1, 1];
+        let indices = [0, 0, 0, 0];
 
         let root: u64 = leaf + elements.iter().sum::<u64>();
 
@@ -95,4 +103,191 @@ mod tests {
             .render(4, &circuit, &root)
             .unwrap();
     }
+
+    #[test]
+    fn regression_merkle_v2() {
+        let leaf = 1u64;
+        let elements = [2u64, 3, 4, 5];
+        let indices = [0u64, 1, 0, 1];
+        let root: u64 = leaf + elements.iter().sum::<u64>();
+
+        let circuit = MerkleTreeV2Circuit {
+            leaf: Value::known(Fp::from(leaf)),
+            elements: elements.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+            indices: indices.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+        };
+        let public_input = vec![Fp::from(leaf), Fp::from(root)];
+
+        crate::regression::assert_vk_fingerprint("merkle_v2", 5, &circuit);
+        crate::regression::assert_golden_proof("merkle_v2", 5, &circuit, &[public_input]);
+    }
+
+    /// Native reference: the root of the path from `leaf` through `elements`, where
+    /// `indices[i]` puts the digest on the right, hashed with the chip's `a + b`.
+    /// That hash is commutative, so the root does not depend on `indices`;
+    /// `swap_matches_index` checks them instead.
+    fn merkle_root(leaf: Fp, elements: &[Fp], indices: &[bool]) -> Fp {
+        let hash2 = |left: Fp, right: Fp| left + right;
+        elements
+            .iter()
+            .zip(indices)
+            .fold(leaf, |digest, (element, swap)| {
+                if *swap {
+                    hash2(*element, digest)
+                } else {
+                    hash2(digest, *element)
+                }
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn merkle_v2_matches_reference(
+            leaf in any::<u64>(),
+            path in prop::collection::vec((any::<u64>(), any::<bool>()), 1..=6),
+        ) {
+            let leaf = Fp::from(leaf);
+            let elements: Vec<_> = path.iter().map(|(element, _)| Fp::from(*element)).collect();
+            let indices: Vec<_> = path.iter().map(|(_, swap)| *swap).collect();
+            let root = merkle_root(leaf, &elements, &indices);
+
+            let circuit = MerkleTreeV2Circuit {
+                leaf: Value::known(leaf),
+                elements: elements.iter().map(|x| Value::known(*x)).collect(),
+                indices: indices.iter().map(|x| Value::known(Fp::from(*x as u64))).collect(),
+            };
+
+            let prover = MockProver::run(5, &circuit, vec![vec![leaf, root]]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            let prover = MockProver::run(5, &circuit, vec![vec![leaf, root + Fp::one()]]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    /// Lays out one `merkle_prove_leaf` region, with `left` and `right` swapped when
+    /// `swap` is set, whatever `index` says.
+    struct SwapCircuit {
+        digest: Fp,
+        element: Fp,
+        index: bool,
+        swap: bool,
+    }
+
+    impl Circuit<Fp> for SwapCircuit {
+        type Config = MerkleTreeV2Config;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { ..*self }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            MerkleTreeV2Chip::configure(meta, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "merkle_prove_leaf",
+                |mut region| {
+                    let [a, b, c] = config.advice;
+                    let (left, right) = if self.swap {
+                        (self.element, self.digest)
+                    } else {
+                        (self.digest, self.element)
+                    };
+                    let index = Fp::from(self.index as u64);
+                    region.assign_advice(|| "digest", a, 0, || Value::known(self.digest))?;
+                    region.assign_advice(|| "element", b, 0, || Value::known(self.element))?;
+                    region.assign_advice(|| "index", c, 0, || Value::known(index))?;
+                    config.bool_selector.enable(&mut region, 0)?;
+                    config.swap_selector.enable(&mut region, 0)?;
+                    region.assign_advice(|| "left", a, 1, || Value::known(left))?;
+                    region.assign_advice(|| "right", b, 1, || Value::known(right))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn swap_matches_index(
+            digest in any::<u64>(),
+            element in any::<u64>(),
+            index in any::<bool>(),
+        ) {
+            prop_assume!(digest != element);
+            let (digest, element) = (Fp::from(digest), Fp::from(element));
+
+            let circuit = SwapCircuit { digest, element, index, swap: index };
+            let prover = MockProver::run(4, &circuit, vec![vec![]]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            // Flipping the index without swapping the pair breaks the swap gate.
+            let circuit = SwapCircuit { digest, element, index: !index, swap: index };
+            let prover = MockProver::run(4, &circuit, vec![vec![]]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn under_constrained_merkle_v2() {
+        let circuit = MerkleTreeV2Circuit {
+            leaf: Value::known(Fp::from(1)),
+            elements: vec![Value::known(Fp::from(2)); 4],
+            indices: vec![Value::known(Fp::from(0)); 4],
+        };
+        let report = crate::analysis::under_constrained::analyze(5, &circuit).unwrap();
+
+        // `Hash2Chip::hash2` never enables its selector, so every hash output is a
+        // free witness that is only copied into the next layer.
+        assert_eq!(report.unused_selectors.len(), 1, "{}", report);
+        assert_eq!(report.unused_selectors[0].constraints, vec!["hash"], "{}", report);
+        let outputs = report
+            .copy_only_cells
+            .iter()
+            .filter(|cell| cell.annotation == "output")
+            .count();
+        assert_eq!(outputs, 4, "{}", report);
+        assert!(report.unreferenced_cells.is_empty(), "{}", report);
+        assert!(report.unused_columns.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_merkle_v2() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MerkleTreeV2Circuit {
+            leaf: Value::known(Fp::from(1)),
+            elements: vec![Value::known(Fp::from(2)); 4],
+            indices: vec![Value::known(Fp::from(0)); 4],
+        };
+        let public_input = vec![Fp::from(1), Fp::from(9)];
+        let report = fuzz(5, &circuit, vec![public_input], Mutations::Pairs).unwrap();
+
+        // The swap gate is a single polynomial, so it only fixes `left - right`: the
+        // prover can shift both by the same amount. With the hash gate disabled, the
+        // shifted pair is never checked against the next digest either.
+        assert!(report.free_cells.is_empty(), "{}", report);
+        for layer in 0..4 {
+            let region = format!("merkle_prove/merkle_prove_layer_{}/merkle_prove_leaf", layer);
+            assert!(
+                report.free_pairs.iter().any(|(a, b)| {
+                    a.region == region
+                        && a.annotation == "left"
+                        && b.region == region
+                        && b.annotation == "right"
+                }),
+                "{}",
+                report
+            );
+        }
+    }
 }
\ No newline at end of file
@@ -53,6 +53,10 @@ impl<F: FieldExt> Circuit<F> for MyMIPCircuit<F> {
 #[allow(dead_code)]
 #[cfg(test)]
 mod tests {
+    // There are no regression vectors for `MyMIPCircuit`: `MyMIPChip` never enables
+    // equality on its instance column, so synthesis fails with `ColumnNotInPermutation`
+    // and there is no verifying key to pin.
+
     use std::marker::PhantomData;
 
     use super::MyMIPCircuit;
@@ -87,4 +91,24 @@ mod tests {
         // uncomment the following line and the assert will fail
         // _prover.assert_satisfied();
     }
+
+    #[test]
+    fn under_constrained_mymip() {
+        let circuit = MyMIPCircuit {
+            leaf: Fp::from(1),
+            proof: vec![Fp::from(1); 5],
+        };
+        let report = crate::analysis::under_constrained::analyze(3, &circuit).unwrap();
+
+        // `MyMIPChip` allocates a `root` column but never assigns or queries it.
+        assert_eq!(
+            report.unused_columns,
+            vec![crate::analysis::ColumnRef {
+                column_type: halo2_proofs::plonk::Any::Advice,
+                index: 1,
+            }],
+            "{}",
+            report
+        );
+    }
 }
@@ -92,6 +92,47 @@ mod tests {
         prover.assert_satisfied();
     }
 
+    #[test]
+    fn under_constrained_mymip_v2() {
+        let circuit = MyMIPCircuitV2 {
+            start_leaf: Value::known(Fp::from(1)),
+            elements: vec![Value::known(Fp::from(1)); 6],
+            indices: vec![Value::known(Fp::from(0)); 6],
+        };
+        let report = crate::analysis::under_constrained::analyze(5, &circuit).unwrap();
+
+        // Shares `Hash2Chip` with `merkle_v2`, and with it the unconstrained hash.
+        assert_eq!(report.unused_selectors.len(), 1, "{}", report);
+        assert_eq!(report.unused_selectors[0].constraints, vec!["hash"], "{}", report);
+        let outputs = report
+            .copy_only_cells
+            .iter()
+            .filter(|cell| cell.annotation == "output")
+            .count();
+        assert_eq!(outputs, 6, "{}", report);
+    }
+
+    #[test]
+    fn regression_mymip_v2() {
+        let start_leaf = 1u64;
+        let elements = [2u64, 3, 4, 5, 6, 7];
+        let indices = [0u64, 1, 0, 1, 0, 1];
+        let root: u64 = start_leaf + elements.iter().sum::<u64>();
+
+        let circuit = MyMIPCircuitV2 {
+            start_leaf: Value::known(Fp::from(start_leaf)),
+            elements: elements.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+            indices: indices.iter().map(|x| Value::known(Fp::from(*x))).collect(),
+        };
+        // The root is exposed at instance row `elements.len()`, not 1.
+        let mut public_input = vec![Fp::zero(); elements.len() + 1];
+        public_input[0] = Fp::from(start_leaf);
+        public_input[elements.len()] = Fp::from(root);
+
+        crate::regression::assert_vk_fingerprint("mymip_v2", 5, &circuit);
+        crate::regression::assert_golden_proof("mymip_v2", 5, &circuit, &[public_input]);
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn plot_mymip_v2() {
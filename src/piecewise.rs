@@ -0,0 +1,594 @@
+use std::ops::{Add, Mul, Sub};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use crate::{
+    comparison::ComparisonConfig,
+    is_zero::{IsZeroChip, IsZeroConfig},
+};
+
+/// An arithmetic expression over the inputs of a `PiecewiseChip`, which can be turned
+/// into a circuit `Expression` or evaluated natively.
+#[derive(Clone, Debug)]
+pub enum Term {
+    Input(usize),
+    Constant(u64),
+    Sum(Box<Term>, Box<Term>),
+    Difference(Box<Term>, Box<Term>),
+    Product(Box<Term>, Box<Term>),
+}
+
+impl Term {
+    pub fn input(index: usize) -> Self {
+        Term::Input(index)
+    }
+
+    pub fn constant(value: u64) -> Self {
+        Term::Constant(value)
+    }
+
+    pub(crate) fn expression<F: FieldExt>(&self, inputs: &[Expression<F>]) -> Expression<F> {
+        match self {
+            Term::Input(index) => inputs[*index].clone(),
+            Term::Constant(value) => Expression::Constant(F::from(*value)),
+            Term::Sum(a, b) => a.expression(inputs) + b.expression(inputs),
+            Term::Difference(a, b) => a.expression(inputs) - b.expression(inputs),
+            Term::Product(a, b) => a.expression(inputs) * b.expression(inputs),
+        }
+    }
+
+    pub fn evaluate<F: FieldExt>(&self, inputs: &[F]) -> F {
+        match self {
+            Term::Input(index) => inputs[*index],
+            Term::Constant(value) => F::from(*value),
+            Term::Sum(a, b) => a.evaluate(inputs) + b.evaluate(inputs),
+            Term::Difference(a, b) => a.evaluate(inputs) - b.evaluate(inputs),
+            Term::Product(a, b) => a.evaluate(inputs) * b.evaluate(inputs),
+        }
+    }
+
+    fn inputs(&self) -> Vec<usize> {
+        match self {
+            Term::Input(index) => vec![*index],
+            Term::Constant(_) => vec![],
+            Term::Sum(a, b) | Term::Difference(a, b) | Term::Product(a, b) => {
+                [a.inputs(), b.inputs()].concat()
+            }
+        }
+    }
+}
+
+impl Add for Term {
+    type Output = Term;
+
+    fn add(self, other: Term) -> Term {
+        Term::Sum(Box::new(self), Box::new(other))
+    }
+}
+
+impl Sub for Term {
+    type Output = Term;
+
+    fn sub(self, other: Term) -> Term {
+        Term::Difference(Box::new(self), Box::new(other))
+    }
+}
+
+impl Mul for Term {
+    type Output = Term;
+
+    fn mul(self, other: Term) -> Term {
+        Term::Product(Box::new(self), Box::new(other))
+    }
+}
+
+/// When a branch applies.
+#[derive(Clone, Debug)]
+pub enum Condition {
+    IsZero(Term),
+    Equal(Term, Term),
+    /// `inputs[a] < inputs[b]`, for inputs of at most `BITS` bits.
+    LessThan(usize, usize),
+    /// `inputs[a] <= inputs[b]`, for inputs of at most `BITS` bits.
+    LessOrEqual(usize, usize),
+    Always,
+}
+
+impl Condition {
+    fn holds<F: FieldExt>(&self, inputs: &[F]) -> bool {
+        match self {
+            Condition::IsZero(term) => term.evaluate(inputs) == F::zero(),
+            Condition::Equal(a, b) => a.evaluate(inputs) == b.evaluate(inputs),
+            Condition::LessThan(a, b) => inputs[*a].get_lower_128() < inputs[*b].get_lower_128(),
+            Condition::LessOrEqual(a, b) => {
+                inputs[*a].get_lower_128() <= inputs[*b].get_lower_128()
+            }
+            Condition::Always => true,
+        }
+    }
+
+    fn inputs(&self) -> Vec<usize> {
+        match self {
+            Condition::IsZero(term) => term.inputs(),
+            Condition::Equal(a, b) => [a.inputs(), b.inputs()].concat(),
+            Condition::LessThan(a, b) | Condition::LessOrEqual(a, b) => vec![*a, *b],
+            Condition::Always => vec![],
+        }
+    }
+}
+
+/// `output` if `condition` holds and no earlier branch applies.
+#[derive(Clone, Debug)]
+pub struct Branch {
+    pub condition: Condition,
+    pub output: Term,
+}
+
+impl Branch {
+    pub fn new(condition: Condition, output: Term) -> Self {
+        Self { condition, output }
+    }
+}
+
+/// Evaluates the piecewise function given by `branches` natively, returning the
+/// index of the branch that applies and its output, or `None` if no branch does.
+pub fn evaluate<F: FieldExt>(branches: &[Branch], inputs: &[F]) -> Option<(usize, F)> {
+    branches
+        .iter()
+        .position(|branch| branch.condition.holds(inputs))
+        .map(|index| (index, branches[index].output.evaluate(inputs)))
+}
+
+/// How the circuit reads the condition of a branch.
+#[derive(Clone, Debug)]
+enum ConditionConfig<F> {
+    /// The `IsZeroChip` on the term that must be zero.
+    IsZero(IsZeroConfig<F>),
+    /// A cell holding a bit copied from the comparison chip.
+    Bit(Column<Advice>),
+    Always,
+}
+
+#[derive(Clone, Debug)]
+pub struct PiecewiseConfig<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize> {
+    selector: Selector,
+    inputs: Vec<Column<Advice>>,
+    output: Column<Advice>,
+    active: Vec<Column<Advice>>,
+    branches: Vec<Branch>,
+    conditions: Vec<ConditionConfig<F>>,
+    comparison: Option<ComparisonConfig<F, BITS, LOOKUP_RANGE>>,
+}
+
+/// Computes a function of its inputs that is defined by cases, like
+///
+///   f(a, b, c) = if a == b { c } else { a - b }
+///
+/// from `FunctionChip`. The branches are tried in order and the first one whose
+/// condition holds applies, so a final `Condition::Always` plays the role of `else`.
+///
+///  inputs  | output | active_0 .. active_n | is_zero inverses and comparison bits
+/// ---------+--------+----------------------+-------------------------------------
+///  x_0 ..  |  f(x)  |     0 .. 1 .. 0      |  one per `IsZero`/`Equal`/comparison branch
+///
+/// Each `active_i` is boolean, exactly one of them is 1, its condition holds and the
+/// conditions of the branches before it do not, and the output equals the output
+/// term of that branch. Comparison conditions are computed by a `ComparisonConfig`,
+/// which must be passed to `configure`, and copied in as bits.
+pub struct PiecewiseChip<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize> {
+    config: PiecewiseConfig<F, BITS, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const BITS: usize, const LOOKUP_RANGE: usize>
+    PiecewiseChip<F, BITS, LOOKUP_RANGE>
+{
+    pub fn construct(config: PiecewiseConfig<F, BITS, LOOKUP_RANGE>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        num_inputs: usize,
+        branches: Vec<Branch>,
+        comparison: Option<ComparisonConfig<F, BITS, LOOKUP_RANGE>>,
+    ) -> PiecewiseConfig<F, BITS, LOOKUP_RANGE> {
+        assert!(!branches.is_empty(), "a piecewise function needs a branch");
+        for branch in &branches {
+            let inputs = [branch.condition.inputs(), branch.output.inputs()].concat();
+            assert!(
+                inputs.iter().all(|index| *index < num_inputs),
+                "branch {:?} reads an input out of 0..{}",
+                branch,
+                num_inputs
+            );
+        }
+
+        let selector = meta.selector();
+        let inputs: Vec<_> = (0..num_inputs).map(|_| meta.advice_column()).collect();
+        let output = meta.advice_column();
+        let active: Vec<_> = branches.iter().map(|_| meta.advice_column()).collect();
+        for column in inputs.iter().chain([&output]) {
+            meta.enable_equality(*column);
+        }
+
+        let query_inputs = |meta: &mut halo2_proofs::plonk::VirtualCells<'_, F>| {
+            inputs
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect::<Vec<_>>()
+        };
+
+        let conditions: Vec<_> = branches
+            .iter()
+            .map(|branch| {
+                let is_zero = |meta: &mut ConstraintSystem<F>, term: Term| {
+                    let value_inv = meta.advice_column();
+                    ConditionConfig::IsZero(IsZeroChip::configure(
+                        meta,
+                        |meta| meta.query_selector(selector),
+                        |meta| term.expression(&query_inputs(meta)),
+                        value_inv,
+                    ))
+                };
+                match &branch.condition {
+                    Condition::IsZero(term) => is_zero(meta, term.clone()),
+                    Condition::Equal(a, b) => is_zero(meta, a.clone() - b.clone()),
+                    Condition::LessThan(..) | Condition::LessOrEqual(..) => {
+                        assert!(
+                            comparison.is_some(),
+                            "comparison branches need a `ComparisonConfig`"
+                        );
+                        let bit = meta.advice_column();
+                        meta.enable_equality(bit);
+                        ConditionConfig::Bit(bit)
+                    }
+                    Condition::Always => ConditionConfig::Always,
+                }
+            })
+            .collect();
+
+        meta.create_gate("piecewise", |meta| {
+            let s = meta.query_selector(selector);
+            let inputs = query_inputs(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+            let active: Vec<_> = active
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect();
+            let one = || Expression::Constant(F::one());
+            let holds: Vec<_> = conditions
+                .iter()
+                .map(|condition| match condition {
+                    ConditionConfig::IsZero(is_zero) => is_zero.expr(),
+                    ConditionConfig::Bit(bit) => meta.query_advice(*bit, Rotation::cur()),
+                    ConditionConfig::Always => one(),
+                })
+                .collect();
+
+            let mut constraints = vec![];
+            for (i, active) in active.iter().enumerate() {
+                constraints.push((
+                    "active is boolean",
+                    active.clone() * (one() - active.clone()),
+                ));
+                constraints.push((
+                    "active branch holds",
+                    active.clone() * (one() - holds[i].clone()),
+                ));
+                for earlier in &holds[..i] {
+                    constraints.push(("no earlier branch holds", active.clone() * earlier.clone()));
+                }
+            }
+            let num_active = active
+                .iter()
+                .fold(Expression::Constant(F::zero()), |acc, active| {
+                    acc + active.clone()
+                });
+            constraints.push(("one branch is active", num_active - one()));
+            let selected = active
+                .iter()
+                .zip(&branches)
+                .fold(Expression::Constant(F::zero()), |acc, (active, branch)| {
+                    acc + active.clone() * branch.output.expression(&inputs)
+                });
+            constraints.push(("output", output - selected));
+
+            Constraints::with_selector(s, constraints)
+        });
+
+        PiecewiseConfig {
+            selector,
+            inputs,
+            output,
+            active,
+            branches,
+            conditions,
+            comparison,
+        }
+    }
+
+    /// Returns the cell holding the function applied to `inputs`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        assert_eq!(inputs.len(), config.inputs.len());
+
+        let mut bits = vec![];
+        for branch in &config.branches {
+            let comparison = config.comparison.as_ref();
+            let bit = match branch.condition {
+                Condition::LessThan(a, b) => Some(comparison.unwrap().lt(
+                    layouter.namespace(|| "a < b"),
+                    &inputs[a],
+                    &inputs[b],
+                )?),
+                Condition::LessOrEqual(a, b) => Some(comparison.unwrap().le(
+                    layouter.namespace(|| "a <= b"),
+                    &inputs[a],
+                    &inputs[b],
+                )?),
+                _ => None,
+            };
+            bits.push(bit);
+        }
+
+        layouter.assign_region(
+            || "piecewise",
+            |mut region| {
+                config.selector.enable(&mut region, 0)?;
+                for (input, column) in inputs.iter().zip(&config.inputs) {
+                    input.copy_advice(|| "input", &mut region, *column, 0)?;
+                }
+
+                let values: Value<Vec<F>> =
+                    inputs.iter().map(|input| input.value().copied()).collect();
+                let result = values
+                    .as_ref()
+                    .map(|values| evaluate(&config.branches, values));
+                result.error_if_known_and(|result| result.is_none())?;
+
+                for (index, column) in config.active.iter().enumerate() {
+                    let active = result.map(|result| {
+                        F::from(matches!(result, Some((active, _)) if active == index) as u64)
+                    });
+                    region.assign_advice(|| "active", *column, 0, || active)?;
+                }
+
+                for ((branch, condition), bit) in
+                    config.branches.iter().zip(&config.conditions).zip(&bits)
+                {
+                    match (condition, bit) {
+                        (ConditionConfig::IsZero(is_zero), _) => {
+                            let term = match &branch.condition {
+                                Condition::IsZero(term) => term.clone(),
+                                Condition::Equal(a, b) => a.clone() - b.clone(),
+                                _ => unreachable!(),
+                            };
+                            let value = values.as_ref().map(|values| term.evaluate(values));
+                            IsZeroChip::construct(is_zero.clone()).assign(&mut region, 0, value)?;
+                        }
+                        (ConditionConfig::Bit(column), Some(bit)) => {
+                            bit.copy_advice(|| "condition", &mut region, *column, 0)?;
+                        }
+                        _ => {}
+                    }
+                }
+
+                let output = result.map(|result| result.map_or(F::zero(), |(_, output)| output));
+                region.assign_advice(|| "output", config.output, 0, || output)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::marker::PhantomData;
+
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    /// The branches of a piecewise function and how many inputs it takes.
+    trait Spec {
+        const NUM_INPUTS: usize;
+
+        fn branches() -> Vec<Branch>;
+    }
+
+    /// `f(a, b, c) = if a == b { c } else { a - b }`, as in `FunctionChip`.
+    struct Function;
+
+    impl Spec for Function {
+        const NUM_INPUTS: usize = 3;
+
+        fn branches() -> Vec<Branch> {
+            let [a, b, c] = [0, 1, 2].map(Term::input);
+            vec![
+                Branch::new(Condition::Equal(a.clone(), b.clone()), c),
+                Branch::new(Condition::Always, a - b),
+            ]
+        }
+    }
+
+    /// ```text
+    /// f(x, y, z) = if x == 0 { 100 }
+    ///              else if x < y { y - x }
+    ///              else if z <= x { x * x }
+    ///              else { x + z }
+    /// ```
+    struct Mixed;
+
+    impl Spec for Mixed {
+        const NUM_INPUTS: usize = 3;
+
+        fn branches() -> Vec<Branch> {
+            let [x, y, z] = [0, 1, 2].map(Term::input);
+            vec![
+                Branch::new(Condition::IsZero(x.clone()), Term::constant(100)),
+                Branch::new(Condition::LessThan(0, 1), y - x.clone()),
+                Branch::new(Condition::LessOrEqual(2, 0), x.clone() * x.clone()),
+                Branch::new(Condition::Always, x + z),
+            ]
+        }
+    }
+
+    /// Like `Mixed` without the final `else`.
+    struct Partial;
+
+    impl Spec for Partial {
+        const NUM_INPUTS: usize = 3;
+
+        fn branches() -> Vec<Branch> {
+            let mut branches = Mixed::branches();
+            branches.pop();
+            branches
+        }
+    }
+
+    /// Reads the inputs from the first instance rows and exposes the output at the
+    /// row after them.
+    struct MyCircuit<S>(PhantomData<S>);
+
+    impl<S> Default for MyCircuit<S> {
+        fn default() -> Self {
+            Self(PhantomData)
+        }
+    }
+
+    impl<S: Spec> Circuit<Fp> for MyCircuit<S> {
+        type Config = (PiecewiseConfig<Fp, 4, 4>, Column<Advice>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(advice);
+            meta.enable_equality(instance);
+            let comparison = ComparisonConfig::configure(meta);
+            let config =
+                PiecewiseChip::configure(meta, S::NUM_INPUTS, S::branches(), Some(comparison));
+            (config, advice, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, advice, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config
+                .comparison
+                .as_ref()
+                .unwrap()
+                .table
+                .load(&mut layouter)?;
+
+            let inputs = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    (0..S::NUM_INPUTS)
+                        .map(|row| {
+                            region.assign_advice_from_instance(
+                                || "input",
+                                instance,
+                                row,
+                                advice,
+                                row,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()
+                },
+            )?;
+
+            let chip = PiecewiseChip::construct(config);
+            let output = chip.assign(layouter.namespace(|| "f"), &inputs)?;
+            layouter.constrain_instance(output.cell(), instance, S::NUM_INPUTS)
+        }
+    }
+
+    /// Returns whether the circuit verifies, or the error synthesis fails with.
+    fn verify<S: Spec>(inputs: &[u64], output: Fp) -> Result<bool, Error> {
+        let mut instance: Vec<_> = inputs.iter().copied().map(Fp::from).collect();
+        instance.push(output);
+        let prover = MockProver::run(7, &MyCircuit::<S>::default(), vec![instance])?;
+        Ok(prover.verify().is_ok())
+    }
+
+    #[test]
+    fn piecewise_matches_function_chip() {
+        for (a, b, c) in [(10, 12, 15), (12, 10, 15), (7, 7, 3), (0, 0, 0)] {
+            let inputs = [a, b, c].map(Fp::from);
+            let (_, output) = evaluate(&Function::branches(), &inputs).unwrap();
+            let expected = if a == b {
+                Fp::from(c)
+            } else {
+                Fp::from(a) - Fp::from(b)
+            };
+            assert_eq!(output, expected);
+
+            assert!(verify::<Function>(&[a, b, c], output).unwrap());
+            assert!(!verify::<Function>(&[a, b, c], output + Fp::one()).unwrap());
+        }
+    }
+
+    #[test]
+    fn piecewise_mixed_conditions() {
+        let mut taken = [false; 4];
+        for x in 0..6 {
+            for y in 0..6 {
+                for z in 0..6 {
+                    let inputs = [x, y, z];
+                    let (branch, output) =
+                        evaluate(&Mixed::branches(), &inputs.map(Fp::from)).unwrap();
+                    taken[branch] = true;
+
+                    assert!(verify::<Mixed>(&inputs, output).unwrap(), "{:?}", inputs);
+                    assert!(!verify::<Mixed>(&inputs, output + Fp::one()).unwrap());
+                }
+            }
+        }
+        assert_eq!(taken, [true; 4]);
+    }
+
+    #[test]
+    fn piecewise_without_matching_branch() {
+        // x = 5, y = 1, z = 7: no condition of `Partial` holds.
+        assert!(matches!(
+            verify::<Partial>(&[5, 1, 7], Fp::zero()),
+            Err(Error::Synthesis)
+        ));
+        assert!(verify::<Partial>(&[5, 1, 3], Fp::from(25)).unwrap());
+    }
+
+    #[test]
+    fn mutation_piecewise() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let instance = [5, 1, 7, 12].map(Fp::from).to_vec();
+        let report = fuzz(
+            7,
+            &MyCircuit::<Mixed>::default(),
+            vec![instance],
+            Mutations::Pairs,
+        );
+        let report = report.unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -1,3 +1,11 @@
+pub mod bounded;
+pub mod dynamic;
 mod example1;
-mod example2;
-mod example3_broken;
+pub mod example2;
+pub mod example3;
+pub mod interval;
+#[cfg(test)]
+mod reference;
+pub mod running_sum;
+pub mod signed;
+pub mod strategy;
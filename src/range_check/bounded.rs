@@ -0,0 +1,471 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use super::{
+    example2::{RangeConstrained, RangeTableConfig},
+    running_sum::RunningSumConfig,
+};
+use crate::boolean::AssignedBit;
+
+#[derive(Debug, Clone)]
+/// A value in `0..bound`, produced by the BoundedConfig.
+pub struct Bounded<F: FieldExt> {
+    cell: AssignedCell<F, F>,
+    bound: u128,
+}
+
+impl<F: FieldExt> Bounded<F> {
+    pub fn cell(&self) -> &AssignedCell<F, F> {
+        &self.cell
+    }
+
+    /// The value is below `bound`.
+    pub fn bound(&self) -> u128 {
+        self.bound
+    }
+}
+
+/// A value checked by `RangeCheckConfig` is below its `RANGE`.
+impl<F: FieldExt, const RANGE: usize> From<RangeConstrained<F, RANGE>> for Bounded<F> {
+    fn from(value: RangeConstrained<F, RANGE>) -> Self {
+        Self {
+            cell: value.cell().clone().evaluate(),
+            bound: RANGE as u128,
+        }
+    }
+}
+
+/// The number of bits of `x`.
+fn bit_length(x: u128) -> usize {
+    (u128::BITS - x.leading_zeros()) as usize
+}
+
+/// This helper does arithmetic on values with known bounds, tracking the bound of each
+/// result so that nothing wraps around the modulus:
+///
+///   a | b |    c    | lt | shift  | q_add | q_sub | q_mul | q_lt
+///  ---+---+---------+----+--------+-------+-------+-------+------
+///   a | b |  a + b  |    |        |   1   |   0   |   0   |  0
+///   a | b |  a - b  |    |        |   0   |   1   |   0   |  0
+///   a | b |  a * b  |    |        |   0   |   0   |   1   |  0
+///   a | b |   diff  | lt |  2^n   |   0   |   0   |   0   |  1
+///
+/// Every bound is at most `2^max_bits`, and `2 * max_bits` is below the size of the
+/// field, so sums and products of two values never reach the modulus. A sum or product
+/// whose bound would exceed `2^max_bits` is range-checked to `max_bits` bits with
+/// `RunningSumConfig`, which fails the proof if the result is that large; otherwise no
+/// check is needed.
+///
+/// `a - b` is always range-checked to the bits of `a`'s bound, since an underflow
+/// wraps to a value near the modulus. `a < b` is computed like `ComparisonConfig`:
+/// `diff = a - b + lt * 2^n` only fits in `n` bits if `lt` is 1 exactly when `a < b`.
+#[derive(Debug, Clone)]
+pub struct BoundedConfig<F: FieldExt, const LOOKUP_RANGE: usize> {
+    q_add: Selector,
+    q_sub: Selector,
+    q_mul: Selector,
+    q_lt: Selector,
+    advice: [Column<Advice>; 4],
+    shift: Column<Fixed>,
+    max_bits: usize,
+    pub running_sum: RunningSumConfig<F, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const LOOKUP_RANGE: usize> BoundedConfig<F, LOOKUP_RANGE> {
+    /// Panics unless `2 * max_bits` is below `F::NUM_BITS` and `max_bits` fits a `u128`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 4],
+        table: RangeTableConfig<F, LOOKUP_RANGE>,
+        max_bits: usize,
+    ) -> Self {
+        assert!(2 * max_bits < F::NUM_BITS as usize && max_bits < u128::BITS as usize);
+
+        let [q_add, q_sub, q_mul, q_lt] = [(); 4].map(|_| meta.selector());
+        let shift = meta.fixed_column();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let running_sum = RunningSumConfig::configure(meta, advice[0], table);
+
+        let [a, b, c, lt] = advice;
+        let query = move |meta: &mut VirtualCells<'_, F>| {
+            [a, b, c].map(|column| meta.query_advice(column, Rotation::cur()))
+        };
+
+        meta.create_gate("bounded add", |meta| {
+            let q = meta.query_selector(q_add);
+            let [a, b, c] = query(meta);
+            Constraints::with_selector(q, [("c = a + b", a + b - c)])
+        });
+
+        meta.create_gate("bounded sub", |meta| {
+            let q = meta.query_selector(q_sub);
+            let [a, b, c] = query(meta);
+            Constraints::with_selector(q, [("c = a - b", a - b - c)])
+        });
+
+        meta.create_gate("bounded mul", |meta| {
+            let q = meta.query_selector(q_mul);
+            let [a, b, c] = query(meta);
+            Constraints::with_selector(q, [("c = a * b", a * b - c)])
+        });
+
+        meta.create_gate("bounded lt", |meta| {
+            let q = meta.query_selector(q_lt);
+            let [a, b, diff] = query(meta);
+            let lt = meta.query_advice(lt, Rotation::cur());
+            let shift = meta.query_fixed(shift, Rotation::cur());
+            let one = Expression::Constant(F::one());
+            Constraints::with_selector(
+                q,
+                [
+                    ("lt is boolean", lt.clone() * (one - lt.clone())),
+                    ("diff = a - b + lt * 2^n", a - b + lt * shift - diff),
+                ],
+            )
+        });
+
+        Self {
+            q_add,
+            q_sub,
+            q_mul,
+            q_lt,
+            advice,
+            shift,
+            max_bits,
+            running_sum,
+        }
+    }
+
+    /// The largest bound a value may have is `2^max_bits`.
+    pub fn max_bound(&self) -> u128 {
+        1 << self.max_bits
+    }
+
+    /// Witnesses `value` and checks that it fits in `num_bits` bits.
+    pub fn witness(
+        &self,
+        layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<Bounded<F>, Error> {
+        assert!(num_bits <= self.max_bits);
+        let cell = self.running_sum.witness_check(layouter, value, num_bits)?;
+        Ok(Bounded {
+            cell,
+            bound: 1 << num_bits,
+        })
+    }
+
+    pub fn add(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Bounded<F>,
+        b: &Bounded<F>,
+    ) -> Result<Bounded<F>, Error> {
+        let c = self.assign(&mut layouter, "bounded add", self.q_add, a, b, |a, b| a + b)?;
+        let bound = a.bound.checked_add(b.bound - 1);
+        self.check_bound(layouter, c, bound)
+    }
+
+    /// Returns `a - b`, failing the proof if `b > a`.
+    pub fn sub(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Bounded<F>,
+        b: &Bounded<F>,
+    ) -> Result<Bounded<F>, Error> {
+        let c = self.assign(&mut layouter, "bounded sub", self.q_sub, a, b, |a, b| a - b)?;
+        // Without an underflow, `c <= a`.
+        self.running_sum.copy_check(
+            layouter.namespace(|| "underflow"),
+            &c,
+            bit_length(a.bound - 1),
+        )?;
+        Ok(Bounded {
+            cell: c,
+            bound: a.bound,
+        })
+    }
+
+    pub fn mul(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Bounded<F>,
+        b: &Bounded<F>,
+    ) -> Result<Bounded<F>, Error> {
+        let c = self.assign(&mut layouter, "bounded mul", self.q_mul, a, b, |a, b| a * b)?;
+        let bound = (a.bound - 1)
+            .checked_mul(b.bound - 1)
+            .and_then(|max| max.checked_add(1));
+        self.check_bound(layouter, c, bound)
+    }
+
+    /// Returns `a < b`.
+    pub fn lt(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &Bounded<F>,
+        b: &Bounded<F>,
+    ) -> Result<AssignedBit<F>, Error> {
+        let n = bit_length(a.bound.max(b.bound) - 1);
+        let shift = F::from_u128(1 << n);
+        let [a_column, b_column, diff_column, lt_column] = self.advice;
+
+        let (diff, lt) = layouter.assign_region(
+            || "bounded lt",
+            |mut region| {
+                self.q_lt.enable(&mut region, 0)?;
+                region.assign_fixed(|| "2^n", self.shift, 0, || Value::known(shift))?;
+                let a = a.cell.copy_advice(|| "a", &mut region, a_column, 0)?;
+                let b = b.cell.copy_advice(|| "b", &mut region, b_column, 0)?;
+
+                let lt = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| a.get_lower_128() < b.get_lower_128());
+                let lt_value = lt.map(|lt| F::from(lt as u64));
+                let diff = a
+                    .value()
+                    .zip(b.value())
+                    .zip(lt_value)
+                    .map(|((a, b), lt)| *a - b + lt * shift);
+                let diff = region.assign_advice(|| "diff", diff_column, 0, || diff)?;
+                let lt = region.assign_advice(|| "lt", lt_column, 0, || lt_value)?;
+                Ok((diff, AssignedBit::new(lt)))
+            },
+        )?;
+
+        self.running_sum
+            .copy_check(layouter.namespace(|| "diff"), &diff, n)?;
+        Ok(lt)
+    }
+
+    fn assign(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        a: &Bounded<F>,
+        b: &Bounded<F>,
+        op: impl Fn(F, F) -> F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [a_column, b_column, c_column, _] = self.advice;
+
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                selector.enable(&mut region, 0)?;
+                let a = a.cell.copy_advice(|| "a", &mut region, a_column, 0)?;
+                let b = b.cell.copy_advice(|| "b", &mut region, b_column, 0)?;
+                let c = a.value().zip(b.value()).map(|(a, b)| op(*a, *b));
+                region.assign_advice(|| "c", c_column, 0, || c)
+            },
+        )
+    }
+
+    /// Range-checks `c` to `max_bits` bits if its bound, `None` if it overflows a
+    /// `u128`, exceeds `2^max_bits`.
+    fn check_bound(
+        &self,
+        mut layouter: impl Layouter<F>,
+        c: AssignedCell<F, F>,
+        bound: Option<u128>,
+    ) -> Result<Bounded<F>, Error> {
+        match bound {
+            Some(bound) if bound <= self.max_bound() => Ok(Bounded { cell: c, bound }),
+            _ => {
+                self.running_sum.copy_check(
+                    layouter.namespace(|| "overflow"),
+                    &c,
+                    self.max_bits,
+                )?;
+                Ok(Bounded {
+                    cell: c,
+                    bound: self.max_bound(),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    const K: u32 = 9;
+
+    #[derive(Clone, Copy)]
+    enum Op {
+        Add,
+        Sub,
+        Mul,
+        Lt,
+    }
+
+    /// Witnesses `a` and `b` with their number of bits and exposes `a op b`.
+    struct MyCircuit {
+        a: (Value<Fp>, usize),
+        b: (Value<Fp>, usize),
+        op: Op,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (BoundedConfig<Fp, 256>, Column<Instance>);
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                a: (Value::unknown(), self.a.1),
+                b: (Value::unknown(), self.b.1),
+                op: self.op,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let table = RangeTableConfig::configure(meta);
+            (BoundedConfig::configure(meta, advice, table, 16), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.running_sum.table.load(&mut layouter)?;
+
+            let a = config.witness(layouter.namespace(|| "a"), self.a.0, self.a.1)?;
+            let b = config.witness(layouter.namespace(|| "b"), self.b.0, self.b.1)?;
+            let op = layouter.namespace(|| "op");
+            let out = match self.op {
+                Op::Add => config.add(op, &a, &b)?.cell,
+                Op::Sub => config.sub(op, &a, &b)?.cell,
+                Op::Mul => config.mul(op, &a, &b)?.cell,
+                Op::Lt => config.lt(op, &a, &b)?.into(),
+            };
+            layouter.constrain_instance(out.cell(), instance, 0)
+        }
+    }
+
+    fn circuit(op: Op, a: (u64, usize), b: (u64, usize)) -> MyCircuit {
+        MyCircuit {
+            a: (Value::known(Fp::from(a.0)), a.1),
+            b: (Value::known(Fp::from(b.0)), b.1),
+            op,
+        }
+    }
+
+    fn verifies(op: Op, a: (u64, usize), b: (u64, usize), out: Fp) -> bool {
+        MockProver::run(K, &circuit(op, a, b), vec![vec![out]])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn bounded_add() {
+        assert!(verifies(Op::Add, (200, 8), (255, 8), Fp::from(455)));
+        assert!(!verifies(Op::Add, (200, 8), (255, 8), Fp::from(454)));
+
+        // The sum of two 16-bit values may not fit in 16 bits, so it is checked.
+        assert!(verifies(Op::Add, (30000, 16), (35535, 16), Fp::from(65535)));
+        assert!(!verifies(
+            Op::Add,
+            (30000, 16),
+            (35536, 16),
+            Fp::from(65536)
+        ));
+    }
+
+    #[test]
+    fn bounded_mul() {
+        assert!(verifies(Op::Mul, (255, 8), (255, 8), Fp::from(65025)));
+        assert!(!verifies(Op::Mul, (255, 8), (255, 8), Fp::from(65024)));
+
+        assert!(verifies(Op::Mul, (300, 16), (200, 8), Fp::from(60000)));
+        assert!(!verifies(Op::Mul, (300, 16), (300, 16), Fp::from(90000)));
+    }
+
+    #[test]
+    fn bounded_sub() {
+        assert!(verifies(Op::Sub, (200, 8), (55, 8), Fp::from(145)));
+        assert!(verifies(Op::Sub, (200, 16), (200, 8), Fp::zero()));
+
+        // An underflow wraps around the modulus, which fails the check.
+        assert!(!verifies(Op::Sub, (55, 8), (200, 8), -Fp::from(145)));
+        assert!(!verifies(Op::Sub, (0, 16), (1, 1), -Fp::one()));
+    }
+
+    #[test]
+    fn bounded_lt() {
+        let values = [
+            (0, 1),
+            (1, 1),
+            (7, 3),
+            (200, 8),
+            (255, 8),
+            (256, 16),
+            (65535, 16),
+        ];
+        for a in values {
+            for b in values {
+                let lt = Fp::from((a.0 < b.0) as u64);
+                assert!(verifies(Op::Lt, a, b, lt), "{} < {}", a.0, b.0);
+                assert!(!verifies(Op::Lt, a, b, Fp::one() - lt), "{} < {}", a.0, b.0);
+            }
+        }
+    }
+
+    #[test]
+    fn bounded_checks_only_on_overflow() {
+        use crate::analysis::layout::measure;
+
+        // The table, `a`, `b`, the operation and its checks.
+        let regions = |op, a_bits, b_bits| {
+            let circuit = circuit(op, (0, a_bits), (0, b_bits));
+            measure(&circuit).unwrap().regions
+        };
+        assert_eq!(regions(Op::Add, 8, 8), 4);
+        assert_eq!(regions(Op::Add, 15, 15), 4);
+        assert_eq!(regions(Op::Add, 16, 1), 5);
+        assert_eq!(regions(Op::Mul, 8, 8), 4);
+        assert_eq!(regions(Op::Mul, 16, 1), 4);
+        assert_eq!(regions(Op::Mul, 9, 8), 5);
+        assert_eq!(regions(Op::Sub, 8, 8), 5);
+        assert_eq!(regions(Op::Lt, 8, 8), 5);
+    }
+
+    #[test]
+    fn mutation_bounded() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        for (op, out) in [
+            (Op::Add, 300),
+            (Op::Sub, 100),
+            (Op::Mul, 20000),
+            (Op::Lt, 0),
+        ] {
+            let circuit = circuit(op, (200, 8), (100, 8));
+            let report = fuzz(K, &circuit, vec![vec![Fp::from(out)]], Mutations::Single).unwrap();
+            assert!(report.is_empty(), "{}", report);
+        }
+    }
+}
@@ -0,0 +1,429 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use super::example2::RangeTableConfig;
+
+/// The sizes a circuit chooses for its range checks when it is configured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeCheckParams {
+    /// The circuit has `2^k` rows.
+    pub k: u32,
+    /// The table holds the values `0..lookup_range`.
+    pub lookup_range: usize,
+}
+
+/// A lookup table of values from `0..range`, where `range` is chosen at configure time.
+#[derive(Debug, Clone)]
+pub struct DynamicRangeTableConfig<F: FieldExt> {
+    pub value: TableColumn,
+    range: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> DynamicRangeTableConfig<F> {
+    /// Panics if the table does not fit in the usable rows of a circuit with `2^k`
+    /// rows, given the blinding rows of what is configured so far. The layouter fills
+    /// the rest of the table starting from the first row after it, which must be
+    /// usable too. Chips configured
+    /// afterwards can query more rotations and leave fewer usable rows, in which case
+    /// `load` fails with `Error::NotEnoughRowsAvailable`.
+    pub fn configure(meta: &mut ConstraintSystem<F>, params: RangeCheckParams) -> Self {
+        let usable_rows = (1usize << params.k).saturating_sub(meta.blinding_factors() + 1);
+        assert!(
+            params.lookup_range > 0 && params.lookup_range < usable_rows,
+            "a table of {} values does not fit in the {} usable rows of k = {}",
+            params.lookup_range,
+            usable_rows,
+            params.k
+        );
+
+        Self {
+            value: meta.lookup_table_column(),
+            range: params.lookup_range,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn range(&self) -> usize {
+        self.range
+    }
+
+    /// Fails with `Error::NotEnoughRowsAvailable` if the table no longer fits in the
+    /// usable rows of the configured circuit.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load range-check table",
+            |mut table| {
+                for (offset, value) in (0..self.range).enumerate() {
+                    table.assign_cell(
+                        || "value",
+                        self.value,
+                        offset,
+                        || Value::known(F::from(value as u64)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Uses a table of `0..RANGE` that is loaded elsewhere.
+impl<F: FieldExt, const RANGE: usize> From<RangeTableConfig<F, RANGE>>
+    for DynamicRangeTableConfig<F>
+{
+    fn from(table: RangeTableConfig<F, RANGE>) -> Self {
+        Self {
+            value: table.value,
+            range: RANGE,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+/// A value in `0..range`, produced by the DynamicRangeCheckConfig.
+pub struct RangeConstrained<F: FieldExt> {
+    range: usize,
+    assigned_cell: AssignedCell<Assigned<F>, F>,
+}
+
+impl<F: FieldExt> RangeConstrained<F> {
+    pub fn range(&self) -> usize {
+        self.range
+    }
+
+    pub fn cell(&self) -> &AssignedCell<Assigned<F>, F> {
+        &self.assigned_cell
+    }
+}
+
+/// This helper checks that the value witnessed in a given cell is within a range that
+/// is passed to `assign`, with a table sized from `RangeCheckParams`.
+///
+///        value     |  max = range - 1  |   q_lookup  |  table_value  |
+///       ---------------------------------------------------------------
+///          v_0     |        m_0        |      1      |       0       |
+///          v_1     |        m_1        |      1      |       1       |
+///
+/// Both `value` and `max - value` are looked up in the table, so `value <= max` for
+/// any range up to the size of the table. `max` is a fixed column, so one type of
+/// circuit serves every range.
+#[derive(Debug, Clone)]
+pub struct DynamicRangeCheckConfig<F: FieldExt> {
+    q_lookup: Selector,
+    value: Column<Advice>,
+    max: Column<Fixed>,
+    pub table: DynamicRangeTableConfig<F>,
+}
+
+impl<F: FieldExt> DynamicRangeCheckConfig<F> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        params: RangeCheckParams,
+    ) -> Self {
+        let table = DynamicRangeTableConfig::configure(meta, params);
+        Self::configure_with_table(meta, value, table)
+    }
+
+    /// Configures the check against a table that may be shared with other chips.
+    pub fn configure_with_table(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        table: DynamicRangeTableConfig<F>,
+    ) -> Self {
+        let q_lookup = meta.complex_selector();
+        let max = meta.fixed_column();
+
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![(q_lookup * value, table.value)]
+        });
+
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let value = meta.query_advice(value, Rotation::cur());
+            let max = meta.query_fixed(max, Rotation::cur());
+
+            vec![(q_lookup * (max - value), table.value)]
+        });
+
+        Self {
+            q_lookup,
+            value,
+            max,
+            table,
+        }
+    }
+
+    /// Checks that `value` is in `0..range`. Fails with `Error::Synthesis` if `range`
+    /// is empty or larger than the table.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<Assigned<F>>,
+        range: usize,
+    ) -> Result<RangeConstrained<F>, Error> {
+        if range == 0 || range > self.table.range() {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "Assign value for dynamic range check",
+            |mut region| {
+                let offset = 0;
+
+                // Enable q_lookup
+                self.q_lookup.enable(&mut region, offset)?;
+
+                // Assign the largest value in the range
+                let max = Value::known(F::from(range as u64 - 1));
+                region.assign_fixed(|| "max", self.max, offset, || max)?;
+
+                // Assign value
+                let assigned_cell =
+                    region.assign_advice(|| "value", self.value, offset, || value)?;
+
+                Ok(RangeConstrained {
+                    range,
+                    assigned_cell,
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::range_check::reference::{around, in_range};
+
+    const PARAMS: RangeCheckParams = RangeCheckParams {
+        k: 9,
+        lookup_range: 256,
+    };
+
+    /// Checks that each value is in its range, all with the same circuit type.
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt> {
+        values: Vec<(Value<Assigned<F>>, usize)>,
+    }
+
+    impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
+        type Config = DynamicRangeCheckConfig<F>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: self
+                    .values
+                    .iter()
+                    .map(|(_, range)| (Value::unknown(), *range))
+                    .collect(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let value = meta.advice_column();
+            DynamicRangeCheckConfig::configure(meta, value, PARAMS)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            for (value, range) in &self.values {
+                let value = config.assign(layouter.namespace(|| "value"), *value, *range)?;
+                assert_eq!(value.range(), *range);
+            }
+            Ok(())
+        }
+    }
+
+    fn circuit(values: &[(Fp, usize)]) -> MyCircuit<Fp> {
+        MyCircuit {
+            values: values
+                .iter()
+                .map(|(value, range)| (Value::known((*value).into()), *range))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn dynamic_range_check() {
+        // Several ranges in one circuit.
+        let several = circuit(&[
+            (Fp::from(0), 1),
+            (Fp::from(7), 8),
+            (Fp::from(99), 100),
+            (Fp::from(255), 256),
+        ]);
+        let prover = MockProver::run(PARAMS.k, &several, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        for range in [1, 8, 100, 256] {
+            for value in [range as u64, range as u64 + 1, 300] {
+                let circuit = circuit(&[(Fp::from(value), range)]);
+                let prover = MockProver::run(PARAMS.k, &circuit, vec![]).unwrap();
+                assert!(prover.verify().is_err(), "{} in 0..{}", value, range);
+            }
+        }
+    }
+
+    #[test]
+    fn dynamic_range_check_failure() {
+        // 100 is in the table, but not below the range.
+        let circuit = circuit(&[(Fp::from(100), 100)]);
+        let prover = MockProver::run(PARAMS.k, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 1,
+                location: FailureLocation::InRegion {
+                    region: (1, "Assign value for dynamic range check").into(),
+                    offset: 0
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn dynamic_range_check_rejects_range_above_table() {
+        for range in [0, 257] {
+            let circuit = circuit(&[(Fp::from(5), range)]);
+            assert!(MockProver::run(PARAMS.k, &circuit, vec![]).is_err());
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn dynamic_range_table_must_fit_in_k() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let value = meta.advice_column();
+        let params = RangeCheckParams {
+            k: 8,
+            lookup_range: 256,
+        };
+        DynamicRangeCheckConfig::configure(&mut meta, value, params);
+    }
+
+    /// Configures the table with the blinding rows of a single advice query, then a
+    /// gate that queries six rotations of another column, which adds blinding rows.
+    struct LateQueriesCircuit;
+
+    impl Circuit<Fp> for LateQueriesCircuit {
+        type Config = DynamicRangeCheckConfig<Fp>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            let params = RangeCheckParams {
+                k: 8,
+                lookup_range: 249,
+            };
+            let config = DynamicRangeCheckConfig::configure(meta, value, params);
+
+            let other = meta.advice_column();
+            meta.create_gate("rotations", |meta| {
+                (0..6)
+                    .map(|rotation| meta.query_advice(other, Rotation(rotation)))
+                    .collect::<Vec<_>>()
+            });
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)
+        }
+    }
+
+    #[test]
+    fn dynamic_range_table_checked_again_at_load() {
+        // The 250 usable rows at configure time fit the table and the row it is filled
+        // from, the 247 left afterwards do not.
+        let mut meta = ConstraintSystem::<Fp>::default();
+        LateQueriesCircuit::configure(&mut meta);
+        assert_eq!((1 << 8) - (meta.blinding_factors() + 1), 247);
+
+        let prover = MockProver::run(8, &LateQueriesCircuit, vec![]);
+        assert!(matches!(
+            prover,
+            Err(Error::NotEnoughRowsAvailable { current_k: 8 })
+        ));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn dynamic_range_table_needs_its_fill_row() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let value = meta.advice_column();
+        let params = RangeCheckParams {
+            k: 8,
+            lookup_range: 250,
+        };
+        DynamicRangeCheckConfig::configure(&mut meta, value, params);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn dynamic_range_table_must_fit_in_small_k() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let value = meta.advice_column();
+        let params = RangeCheckParams {
+            k: 2,
+            lookup_range: 1,
+        };
+        DynamicRangeCheckConfig::configure(&mut meta, value, params);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn dynamic_range_check_matches_reference(range in 1..=256usize, value in around(256)) {
+            let prover = MockProver::run(PARAMS.k, &circuit(&[(value, range)]), vec![]).unwrap();
+            prop_assert_eq!(prover.verify().is_ok(), in_range(value, range as u64));
+        }
+    }
+
+    #[test]
+    fn mutation_dynamic_range_check() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = circuit(&[(Fp::from(7), 8), (Fp::from(0), 1)]);
+        let report = fuzz(PARAMS.k, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so any other value in 0..8 verifies as well. The value in
+        // 0..1 is pinned.
+        assert_eq!(report.free_cells.len(), 1, "{}", report);
+        assert_eq!(report.free_cells[0].annotation, "value");
+    }
+}
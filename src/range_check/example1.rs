@@ -86,8 +86,10 @@ mod tests {
         pasta::Fp,
         plonk::{Any, Circuit},
     };
+    use proptest::prelude::*;
 
     use super::*;
+    use crate::range_check::reference::{around, in_range};
 
     #[derive(Default)]
     struct MyCircuit<F: FieldExt, const RANGE: usize> {
@@ -153,6 +155,50 @@ mod tests {
         }
     }
 
+    proptest! {
+        #[test]
+        fn range_check_1_matches_reference(value in around(8)) {
+            let circuit = MyCircuit::<Fp, 8> {
+                value: Value::known(value.into()),
+            };
+            let prover = MockProver::run(4, &circuit, vec![]).unwrap();
+            prop_assert_eq!(prover.verify().is_ok(), in_range(value, 8));
+        }
+    }
+
+    #[test]
+    fn regression_range_check_1() {
+        let circuit = MyCircuit::<Fp, 8> {
+            value: Value::known(Fp::from(7).into()),
+        };
+
+        crate::regression::assert_vk_fingerprint("range_check_example1", 4, &circuit);
+        crate::regression::assert_golden_proof("range_check_example1", 4, &circuit, &[]);
+    }
+
+    #[test]
+    fn under_constrained_range_check_1() {
+        let circuit = MyCircuit::<Fp, 8> {
+            value: Value::known(Fp::from(7).into()),
+        };
+        let report = crate::analysis::under_constrained::analyze(4, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_range_check_1() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::<Fp, 8> {
+            value: Value::known(Fp::from(7).into()),
+        };
+        let report = fuzz(4, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so any other value in the range verifies as well.
+        assert_eq!(report.free_cells.len(), 1, "{}", report);
+        assert_eq!(report.free_cells[0].annotation, "value");
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn print_range_check_1() {
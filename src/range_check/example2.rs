@@ -5,8 +5,10 @@ use halo2_proofs::{
     poly::Rotation,
 };
 
+use crate::tables::TableRegistry;
+
 mod table;
-use table::*;
+pub use table::RangeTableConfig;
 
 /// This helper checks that the value witnessed in a given cell is within a given range.
 /// Depending on the range, this helper uses either a range-check expression (for small ranges),
@@ -20,23 +22,48 @@ use table::*;
 
 #[derive(Debug, Clone)]
 /// A range-constrained value in the circuit produced by the RangeCheckConfig.
-struct RangeConstrained<F: FieldExt, const RANGE: usize>(AssignedCell<Assigned<F>, F>);
+pub struct RangeConstrained<F: FieldExt, const RANGE: usize>(AssignedCell<Assigned<F>, F>);
+
+impl<F: FieldExt, const RANGE: usize> RangeConstrained<F, RANGE> {
+    pub fn cell(&self) -> &AssignedCell<Assigned<F>, F> {
+        &self.0
+    }
+}
 
 #[derive(Debug, Clone)]
-struct RangeCheckConfig<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
+pub struct RangeCheckConfig<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
     q_range_check: Selector,
     q_lookup: Selector,
     value: Column<Advice>,
-    table: RangeTableConfig<F, LOOKUP_RANGE>,
+    pub table: RangeTableConfig<F, LOOKUP_RANGE>,
 }
 
 impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize>
     RangeCheckConfig<F, RANGE, LOOKUP_RANGE>
 {
     pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        let table = RangeTableConfig::configure(meta);
+        Self::configure_with_table(meta, value, table)
+    }
+
+    /// Requests the table of `0..LOOKUP_RANGE` from `tables`, which loads it.
+    pub fn configure_with_registry(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        tables: &mut TableRegistry<F>,
+    ) -> Self {
+        let table = RangeTableConfig::configure_with_registry(meta, tables);
+        Self::configure_with_table(meta, value, table)
+    }
+
+    /// Configures the check against a table that may be shared with other chips.
+    pub fn configure_with_table(
+        meta: &mut ConstraintSystem<F>,
+        value: Column<Advice>,
+        table: RangeTableConfig<F, LOOKUP_RANGE>,
+    ) -> Self {
         let q_range_check = meta.selector();
         let q_lookup = meta.complex_selector();
-        let table = RangeTableConfig::configure(meta);
 
         meta.create_gate("range check", |meta| {
             //        value     |    q_range_check
@@ -114,6 +141,58 @@ impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize>
             },
         )
     }
+
+    /// Checks each value with the expression, laid out one per row in a single region.
+    pub fn assign_many(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F, RANGE>>, Error> {
+        self.assign_rows(
+            layouter,
+            "Assign values for simple range check",
+            self.q_range_check,
+            values,
+        )
+    }
+
+    /// Looks up each value in the table, laid out one per row in a single region.
+    pub fn assign_many_lookup(
+        &self,
+        layouter: impl Layouter<F>,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F, LOOKUP_RANGE>>, Error> {
+        self.assign_rows(
+            layouter,
+            "Assign values for lookup range check",
+            self.q_lookup,
+            values,
+        )
+    }
+
+    fn assign_rows<const R: usize>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        selector: Selector,
+        values: &[Value<Assigned<F>>],
+    ) -> Result<Vec<RangeConstrained<F, R>>, Error> {
+        layouter.assign_region(
+            || name,
+            |mut region| {
+                values
+                    .iter()
+                    .enumerate()
+                    .map(|(offset, value)| {
+                        selector.enable(&mut region, offset)?;
+                        region
+                            .assign_advice(|| "value", self.value, offset, || *value)
+                            .map(RangeConstrained)
+                    })
+                    .collect()
+            },
+        )
+    }
 }
 
 #[cfg(test)]
@@ -124,8 +203,10 @@ mod tests {
         pasta::Fp,
         plonk::{Any, Circuit},
     };
+    use proptest::prelude::*;
 
     use super::*;
+    use crate::range_check::reference::{around, in_range};
 
     #[derive(Default)]
     struct MyCircuit<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
@@ -214,6 +295,180 @@ mod tests {
         }
     }
 
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn range_check_2_matches_reference(value in around(8), lookup_value in around(256)) {
+            let circuit = MyCircuit::<Fp, 8, 256> {
+                value: Value::known(value.into()),
+                lookup_value: Value::known(lookup_value.into()),
+            };
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            prop_assert_eq!(
+                prover.verify().is_ok(),
+                in_range(value, 8) && in_range(lookup_value, 256)
+            );
+        }
+    }
+
+    #[test]
+    fn regression_range_check_2() {
+        let circuit = MyCircuit::<Fp, 8, 256> {
+            value: Value::known(Fp::from(7).into()),
+            lookup_value: Value::known(Fp::from(255).into()),
+        };
+
+        crate::regression::assert_vk_fingerprint("range_check_example2", 9, &circuit);
+        crate::regression::assert_golden_proof("range_check_example2", 9, &circuit, &[]);
+    }
+
+    #[test]
+    fn under_constrained_range_check_2() {
+        let circuit = MyCircuit::<Fp, 8, 256> {
+            value: Value::known(Fp::from(7).into()),
+            lookup_value: Value::known(Fp::from(255).into()),
+        };
+        let report = crate::analysis::under_constrained::analyze(9, &circuit).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+
+    #[test]
+    fn mutation_range_check_2() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit::<Fp, 8, 256> {
+            value: Value::known(Fp::from(7).into()),
+            lookup_value: Value::known(Fp::from(255).into()),
+        };
+        let report = fuzz(9, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so any other value in the range verifies as well.
+        assert_eq!(report.free_cells.len(), 2, "{}", report);
+        assert!(report
+            .free_cells
+            .iter()
+            .all(|cell| cell.annotation == "value"));
+    }
+
+    /// Checks every value, each in its own region or all in one region per kind.
+    struct BatchCircuit<const RANGE: usize, const LOOKUP_RANGE: usize> {
+        values: Vec<Value<Assigned<Fp>>>,
+        lookup_values: Vec<Value<Assigned<Fp>>>,
+        batch: bool,
+    }
+
+    impl<const RANGE: usize, const LOOKUP_RANGE: usize> Circuit<Fp>
+        for BatchCircuit<RANGE, LOOKUP_RANGE>
+    {
+        type Config = RangeCheckConfig<Fp, RANGE, LOOKUP_RANGE>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: vec![Value::unknown(); self.values.len()],
+                lookup_values: vec![Value::unknown(); self.lookup_values.len()],
+                batch: self.batch,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckConfig::configure(meta, value)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            if self.batch {
+                let checked = config.assign_many(layouter.namespace(|| "values"), &self.values)?;
+                assert_eq!(checked.len(), self.values.len());
+                let checked = config.assign_many_lookup(
+                    layouter.namespace(|| "lookup values"),
+                    &self.lookup_values,
+                )?;
+                assert_eq!(checked.len(), self.lookup_values.len());
+            } else {
+                for value in &self.values {
+                    config.assign_simple(layouter.namespace(|| "value"), *value)?;
+                }
+                for value in &self.lookup_values {
+                    config.assign_lookup(layouter.namespace(|| "lookup value"), *value)?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn batch_circuit(values: &[u64], lookup_values: &[u64], batch: bool) -> BatchCircuit<8, 256> {
+        let known = |values: &[u64]| {
+            values
+                .iter()
+                .map(|value| Value::known(Fp::from(*value).into()))
+                .collect()
+        };
+        BatchCircuit {
+            values: known(values),
+            lookup_values: known(lookup_values),
+            batch,
+        }
+    }
+
+    #[test]
+    fn batch_range_check_2() {
+        let values: Vec<u64> = (0..8).collect();
+        let lookup_values: Vec<u64> = (0..256).step_by(5).collect();
+        let prover =
+            MockProver::run(9, &batch_circuit(&values, &lookup_values, true), vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // Each row is checked: the failures point at the rows of the bad values.
+        let circuit = batch_circuit(&[1, 2, 8, 3], &[0, 255, 256], true);
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![
+                VerifyFailure::ConstraintNotSatisfied {
+                    constraint: ((0, "range check").into(), 0, "range check").into(),
+                    location: FailureLocation::InRegion {
+                        region: (1, "Assign values for simple range check").into(),
+                        offset: 2
+                    },
+                    cell_values: vec![(((Any::Advice, 0).into(), 0).into(), "0x8".to_string())]
+                },
+                VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion {
+                        region: (2, "Assign values for lookup range check").into(),
+                        offset: 2
+                    }
+                }
+            ])
+        );
+    }
+
+    #[test]
+    fn batch_range_check_2_layout() {
+        use crate::analysis::layout::measure;
+
+        let values: Vec<u64> = (0..100).map(|i| i % 8).collect();
+        let lookup_values: Vec<u64> = (0..100).map(|i| i * 2).collect();
+        let batch = measure(&batch_circuit(&values, &lookup_values, true)).unwrap();
+        let single = measure(&batch_circuit(&values, &lookup_values, false)).unwrap();
+
+        // The table and one region per kind, against a region per value. Each region
+        // only uses the value column, so V1 packs the one-row regions back to back and
+        // both take a row per value.
+        assert_eq!(batch.regions, 3, "{}", batch);
+        assert_eq!(single.regions, 201, "{}", single);
+        assert_eq!(batch.rows, 200, "{}", batch);
+        assert_eq!(single.rows, batch.rows, "{}", single);
+    }
+
     #[cfg(feature = "dev-graph")]
     #[test]
     fn print_range_check_2() {
@@ -6,15 +6,17 @@ use halo2_proofs::{
     plonk::{ConstraintSystem, Error, TableColumn},
 };
 
+use crate::tables::{TableRegistry, TableSpec};
+
 /// A lookup table of values from 0..RANGE.
 #[derive(Debug, Clone)]
-pub(super) struct RangeTableConfig<F: FieldExt, const RANGE: usize> {
-    pub(super) value: TableColumn,
+pub struct RangeTableConfig<F: FieldExt, const RANGE: usize> {
+    pub value: TableColumn,
     _marker: PhantomData<F>,
 }
 
 impl<F: FieldExt, const RANGE: usize> RangeTableConfig<F, RANGE> {
-    pub(super) fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
         let value = meta.lookup_table_column();
 
         Self {
@@ -23,7 +25,23 @@ impl<F: FieldExt, const RANGE: usize> RangeTableConfig<F, RANGE> {
         }
     }
 
-    pub(super) fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+    /// Shares the table with the other chips of `tables`, which loads it in place of
+    /// `load`.
+    pub fn configure_with_registry(
+        meta: &mut ConstraintSystem<F>,
+        tables: &mut TableRegistry<F>,
+    ) -> Self {
+        let [value] = tables.request(meta, TableSpec::Range { range: RANGE })[..] else {
+            unreachable!("range tables have one column")
+        };
+
+        Self {
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
         layouter.assign_table(
             || "load range-check table",
             |mut table| {
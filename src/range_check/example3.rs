@@ -0,0 +1,311 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Fixed, Selector},
+    poly::Rotation,
+};
+
+mod table;
+pub use table::RangeTableConfig;
+
+/// This helper uses a lookup table to check that the value witnessed in a given cell is
+/// below `2^num_bits`, for a `num_bits` chosen per check.
+///
+/// The lookup table is tagged by `num_bits` to give a strict range check.
+///
+///        value     |   num_bits   |   q_lookup  |  table_num_bits  |  table_value  |
+///       ----------------------------------------------------------------------------
+///          v_0     |      n_0     |      1      |        0         |       0       |
+///          v_1     |              |      0      |        1         |       0       |
+///          ...     |              |     ...     |        1         |       1       |
+///          ...     |              |     ...     |        2         |       0       |
+///          ...     |              |     ...     |       ...        |      ...      |
+///
+/// The tag `n` marks every value below `2^n`, so `(n, v)` is in the table exactly when
+/// `v < 2^n`. `num_bits` is a fixed column, so the prover cannot pick a looser range.
+/// Both lookup inputs are multiplied by `q_lookup`, so rows with the lookup disabled
+/// look up `(0, 0)`, which is the first row of the table.
+///
+
+#[derive(Debug, Clone)]
+/// A range-constrained value in the circuit produced by the RangeCheckConfig.
+pub struct RangeConstrained<F: FieldExt> {
+    num_bits: usize,
+    assigned_cell: AssignedCell<Assigned<F>, F>,
+}
+
+impl<F: FieldExt> RangeConstrained<F> {
+    /// The value is below `2^num_bits`.
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    pub fn cell(&self) -> &AssignedCell<Assigned<F>, F> {
+        &self.assigned_cell
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig<F: FieldExt, const NUM_BITS: usize> {
+    q_lookup: Selector,
+    num_bits: Column<Fixed>,
+    value: Column<Advice>,
+    pub table: RangeTableConfig<F, NUM_BITS>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> RangeCheckConfig<F, NUM_BITS> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        num_bits: Column<Fixed>,
+        value: Column<Advice>,
+    ) -> Self {
+        let q_lookup = meta.complex_selector();
+        let table = RangeTableConfig::configure(meta);
+
+        meta.lookup(|meta| {
+            let q_lookup = meta.query_selector(q_lookup);
+            let num_bits = meta.query_fixed(num_bits, Rotation::cur());
+            let value = meta.query_advice(value, Rotation::cur());
+
+            vec![
+                (q_lookup.clone() * num_bits, table.num_bits),
+                (q_lookup * value, table.value),
+            ]
+        });
+
+        Self {
+            q_lookup,
+            num_bits,
+            value,
+            table,
+        }
+    }
+
+    /// Checks that `value < 2^num_bits`. Fails with `Error::Synthesis` if `num_bits`
+    /// is larger than the table.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        num_bits: usize,
+        value: Value<Assigned<F>>,
+    ) -> Result<RangeConstrained<F>, Error> {
+        if num_bits > NUM_BITS {
+            return Err(Error::Synthesis);
+        }
+
+        layouter.assign_region(
+            || "Assign value",
+            |mut region| {
+                let offset = 0;
+
+                // Enable q_lookup
+                self.q_lookup.enable(&mut region, offset)?;
+
+                // Assign num_bits
+                region.assign_fixed(
+                    || "num_bits",
+                    self.num_bits,
+                    offset,
+                    || Value::known(F::from(num_bits as u64)),
+                )?;
+
+                // Assign value
+                let assigned_cell =
+                    region.assign_advice(|| "value", self.value, offset, || value)?;
+
+                Ok(RangeConstrained {
+                    num_bits,
+                    assigned_cell,
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+
+    /// Checks that `value < 2^num_bits`, then fills a row of the columns with the
+    /// lookup disabled.
+    #[derive(Default)]
+    struct MyCircuit<F: FieldExt, const NUM_BITS: usize> {
+        num_bits: usize,
+        value: Value<Assigned<F>>,
+        /// Written to the value column on the row with the lookup disabled.
+        disabled_value: Value<Assigned<F>>,
+    }
+
+    impl<F: FieldExt, const NUM_BITS: usize> Circuit<F> for MyCircuit<F, NUM_BITS> {
+        type Config = RangeCheckConfig<F, NUM_BITS>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                num_bits: self.num_bits,
+                ..Self::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let num_bits = meta.fixed_column();
+            let value = meta.advice_column();
+            RangeCheckConfig::configure(meta, num_bits, value)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            let value = config.assign(
+                layouter.namespace(|| "Assign value"),
+                self.num_bits,
+                self.value,
+            )?;
+            assert_eq!(value.num_bits(), self.num_bits);
+
+            layouter.assign_region(
+                || "Lookup disabled",
+                |mut region| {
+                    region.assign_fixed(
+                        || "num_bits",
+                        config.num_bits,
+                        0,
+                        || Value::known(F::from(NUM_BITS as u64 + 1)),
+                    )?;
+                    region.assign_advice(|| "value", config.value, 0, || self.disabled_value)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    const K: u32 = 10;
+
+    fn circuit(num_bits: usize, value: Fp) -> MyCircuit<Fp, 8> {
+        MyCircuit {
+            num_bits,
+            value: Value::known(value.into()),
+            disabled_value: Value::known(Fp::from(1000).into()),
+        }
+    }
+
+    #[test]
+    fn test_range_check_3() {
+        // Successful cases
+        for num_bits in 0..=8 {
+            for value in 0..(1 << num_bits) {
+                let circuit = circuit(num_bits, Fp::from(value));
+                let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+                prover.assert_satisfied();
+            }
+        }
+    }
+
+    #[test]
+    fn regression_range_check_3() {
+        let circuit = circuit(5, Fp::from(20));
+
+        crate::regression::assert_vk_fingerprint("range_check_example3", K, &circuit);
+        crate::regression::assert_golden_proof("range_check_example3", K, &circuit, &[]);
+    }
+
+    #[test]
+    fn range_check_3_boundaries() {
+        for num_bits in 0..=8 {
+            let bound = 1u64 << num_bits;
+            for value in [bound, bound + 1, 2 * bound, 256, 1000] {
+                if value < bound {
+                    continue;
+                }
+                let circuit = circuit(num_bits, Fp::from(value));
+                let prover = MockProver::run(K, &circuit, vec![]).unwrap();
+                assert_eq!(
+                    prover.verify(),
+                    Err(vec![VerifyFailure::Lookup {
+                        lookup_index: 0,
+                        location: FailureLocation::InRegion {
+                            region: (1, "Assign value").into(),
+                            offset: 0
+                        }
+                    }]),
+                    "{} in {} bits",
+                    value,
+                    num_bits
+                );
+            }
+        }
+
+        // Values that wrap around the modulus.
+        for value in [-Fp::one(), -Fp::from(255)] {
+            let prover = MockProver::run(K, &circuit(8, value), vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn range_check_3_rejects_num_bits_above_table() {
+        let circuit = circuit(9, Fp::from(300));
+        assert!(MockProver::run(K, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn under_constrained_range_check_3() {
+        let circuit = circuit(3, Fp::from(5));
+        let report = crate::analysis::under_constrained::analyze(K, &circuit).unwrap();
+
+        // The row with the lookup disabled is read by nothing.
+        let unused: Vec<_> = report
+            .unreferenced_cells
+            .iter()
+            .map(|cell| cell.annotation.as_str())
+            .collect();
+        assert_eq!(unused, ["value"], "{}", report);
+    }
+
+    #[test]
+    fn mutation_range_check_3() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let report = fuzz(K, &circuit(3, Fp::from(5)), vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so any other value in the range verifies as well, and the
+        // row with the lookup disabled takes any value.
+        assert_eq!(report.free_cells.len(), 2, "{}", report);
+        assert!(report
+            .free_cells
+            .iter()
+            .all(|cell| cell.annotation == "value"));
+    }
+
+    #[cfg(feature = "dev-graph")]
+    #[test]
+    fn print_range_check_3() {
+        use plotters::prelude::*;
+
+        let root = BitMapBackend::new("range-check-3-layout.png", (1024, 3096)).into_drawing_area();
+        root.fill(&WHITE).unwrap();
+        let root = root
+            .titled("Range Check 3 Layout", ("sans-serif", 60))
+            .unwrap();
+
+        let circuit = MyCircuit::<Fp, 8> {
+            num_bits: 3,
+            value: Value::unknown(),
+            disabled_value: Value::unknown(),
+        };
+        halo2_proofs::dev::CircuitLayout::default()
+            .render(K, &circuit, &root)
+            .unwrap();
+    }
+}
@@ -0,0 +1,68 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+/// A lookup table of `(num_bits, value)` for every `num_bits` in `0..=NUM_BITS` and
+/// every `value < 2^num_bits`, e.g. for NUM_BITS = 2:
+///
+///   num_bits | value
+///  ----------+-------
+///      0     |   0
+///      1     |   0
+///      1     |   1
+///      2     |   0
+///     ..     |  ..
+///      2     |   3
+///
+/// The row `(0, 0)` is what rows with the lookup disabled look up.
+#[derive(Debug, Clone)]
+pub struct RangeTableConfig<F: FieldExt, const NUM_BITS: usize> {
+    pub num_bits: TableColumn,
+    pub value: TableColumn,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> RangeTableConfig<F, NUM_BITS> {
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let num_bits = meta.lookup_table_column();
+        let value = meta.lookup_table_column();
+
+        Self {
+            num_bits,
+            value,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load range-check table",
+            |mut table| {
+                let mut offset = 0;
+                for num_bits in 0..=NUM_BITS {
+                    for value in 0..(1 << num_bits) {
+                        table.assign_cell(
+                            || "assign num_bits",
+                            self.num_bits,
+                            offset,
+                            || Value::known(F::from(num_bits as u64)),
+                        )?;
+                        table.assign_cell(
+                            || "assign value",
+                            self.value,
+                            offset,
+                            || Value::known(F::from(value as u64)),
+                        )?;
+                        offset += 1;
+                    }
+                }
+
+                Ok(())
+            },
+        )
+    }
+}
@@ -0,0 +1,456 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Selector},
+    poly::Rotation,
+};
+
+use super::{example2::RangeTableConfig, running_sum::RunningSumConfig};
+
+/// A bound of an interval: a constant of the circuit, or a cell.
+#[derive(Debug, Clone)]
+pub enum Bound<F: FieldExt> {
+    Constant(F),
+    Cell(AssignedCell<F, F>),
+}
+
+#[derive(Debug, Clone)]
+/// A value in `lo..hi`, produced by the IntervalConfig.
+pub struct RangeConstrained<F: FieldExt> {
+    value: AssignedCell<F, F>,
+    lo: Bound<F>,
+    hi: Bound<F>,
+}
+
+impl<F: FieldExt> RangeConstrained<F> {
+    pub fn value(&self) -> &AssignedCell<F, F> {
+        &self.value
+    }
+
+    /// Returns `(lo, hi)`, where `lo` is included and `hi` is not.
+    pub fn interval(&self) -> (&Bound<F>, &Bound<F>) {
+        (&self.lo, &self.hi)
+    }
+}
+
+/// This helper checks that a value is in the interval `lo..hi`, where the bounds are
+/// constants or cells.
+///
+///   value | lo | hi |   above   |     below      | q_interval
+///  -------+----+----+-----------+----------------+------------
+///     v   | lo | hi |  v - lo   | hi - 1 - v     |     1
+///
+/// `above` and `below` are range-checked to `num_bits` bits with `RunningSumConfig`.
+/// They add up to `hi - 1 - lo`, so if `hi - lo <= 2^num_bits` and `2^(num_bits + 1)`
+/// is below the modulus, both are at most `hi - 1 - lo` and `v` is in the interval. The
+/// interval is taken in the field, so `p - 5..5` holds the ten values around 0.
+///
+/// With constant bounds, `num_bits` is the fewest bits that fit `hi - 1 - lo`. With
+/// cells as bounds, it is chosen by the caller, and values more than `2^num_bits` away
+/// from both bounds are refused even if they are in the interval.
+#[derive(Debug, Clone)]
+pub struct IntervalConfig<F: FieldExt, const LOOKUP_RANGE: usize> {
+    q_interval: Selector,
+    advice: [Column<Advice>; 5],
+    running_sum: RunningSumConfig<F, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const LOOKUP_RANGE: usize> IntervalConfig<F, LOOKUP_RANGE> {
+    /// Constant bounds need the circuit to enable a fixed column with
+    /// `meta.enable_constant`.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        table: RangeTableConfig<F, LOOKUP_RANGE>,
+    ) -> Self {
+        let q_interval = meta.selector();
+        let advice = [(); 5].map(|_| meta.advice_column());
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let running_sum = RunningSumConfig::configure(meta, advice[0], table);
+
+        meta.create_gate("interval", |meta| {
+            let q = meta.query_selector(q_interval);
+            let [value, lo, hi, above, below] =
+                advice.map(|column| meta.query_advice(column, Rotation::cur()));
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q,
+                [
+                    ("above = value - lo", value.clone() - lo - above),
+                    ("below = hi - 1 - value", hi - one - value - below),
+                ],
+            )
+        });
+
+        Self {
+            q_interval,
+            advice,
+            running_sum,
+        }
+    }
+
+    pub fn table(&self) -> &RangeTableConfig<F, LOOKUP_RANGE> {
+        &self.running_sum.table
+    }
+
+    /// Checks that `value` is in `lo..hi` for constant bounds. Fails with
+    /// `Error::Synthesis` if the interval is empty or too wide to check.
+    pub fn assert_in_interval(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        lo: F,
+        hi: F,
+    ) -> Result<RangeConstrained<F>, Error> {
+        let num_bits = bit_length(&(hi - F::one() - lo));
+        if lo == hi || num_bits + 1 >= F::NUM_BITS as usize {
+            return Err(Error::Synthesis);
+        }
+
+        let [_, lo_column, hi_column, _, _] = self.advice;
+        let (lo_cell, hi_cell) = layouter.assign_region(
+            || "interval bounds",
+            |mut region| {
+                let lo = region.assign_advice_from_constant(|| "lo", lo_column, 0, lo)?;
+                let hi = region.assign_advice_from_constant(|| "hi", hi_column, 0, hi)?;
+                Ok((lo, hi))
+            },
+        )?;
+        let value = self.assign(layouter, value, &lo_cell, &hi_cell, num_bits)?;
+
+        Ok(RangeConstrained {
+            value,
+            lo: Bound::Constant(lo),
+            hi: Bound::Constant(hi),
+        })
+    }
+
+    /// Checks that `value` is in `lo..hi` and at most `2^num_bits - 1` away from
+    /// both bounds.
+    pub fn assert_in_interval_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        lo: &AssignedCell<F, F>,
+        hi: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<RangeConstrained<F>, Error> {
+        assert!(num_bits + 1 < F::NUM_BITS as usize);
+        let value = self.assign(layouter, value, lo, hi, num_bits)?;
+
+        Ok(RangeConstrained {
+            value,
+            lo: Bound::Cell(lo.clone()),
+            hi: Bound::Cell(hi.clone()),
+        })
+    }
+
+    fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        lo: &AssignedCell<F, F>,
+        hi: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [value_column, lo_column, hi_column, above_column, below_column] = self.advice;
+
+        let (value, above, below) = layouter.assign_region(
+            || "interval",
+            |mut region| {
+                self.q_interval.enable(&mut region, 0)?;
+                let value = value.copy_advice(|| "value", &mut region, value_column, 0)?;
+                lo.copy_advice(|| "lo", &mut region, lo_column, 0)?;
+                hi.copy_advice(|| "hi", &mut region, hi_column, 0)?;
+
+                let above = value.value().zip(lo.value()).map(|(v, lo)| *v - lo);
+                let below = value
+                    .value()
+                    .zip(hi.value())
+                    .map(|(v, hi)| *hi - F::one() - v);
+                let above = region.assign_advice(|| "value - lo", above_column, 0, || above)?;
+                let below = region.assign_advice(|| "hi - 1 - value", below_column, 0, || below)?;
+                Ok((value, above, below))
+            },
+        )?;
+
+        self.running_sum
+            .copy_check(layouter.namespace(|| "value - lo"), &above, num_bits)?;
+        self.running_sum
+            .copy_check(layouter.namespace(|| "hi - 1 - value"), &below, num_bits)?;
+        Ok(value)
+    }
+}
+
+/// The number of bits of `x`, as an integer below the modulus.
+pub(super) fn bit_length<F: FieldExt>(x: &F) -> usize {
+    let repr = x.to_repr();
+    let bytes = repr.as_ref();
+    (0..bytes.len() * 8)
+        .rev()
+        .find(|i| (bytes[i / 8] >> (i % 8)) & 1 == 1)
+        .map_or(0, |i| i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::{floor_planner::V1, Value},
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Column, Instance},
+    };
+
+    use super::*;
+
+    const K: u32 = 9;
+
+    #[derive(Clone, Copy)]
+    enum Bounds {
+        Constant(Fp, Fp),
+        /// `lo` and `hi` are the public inputs, checked with the given `num_bits`.
+        Public(usize),
+    }
+
+    struct MyCircuit {
+        value: Value<Fp>,
+        bounds: Bounds,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (IntervalConfig<Fp, 256>, Column<Instance>);
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                bounds: self.bounds,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let constants = meta.fixed_column();
+            meta.enable_constant(constants);
+            let table = RangeTableConfig::configure(meta);
+            (IntervalConfig::configure(meta, table), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table().load(&mut layouter)?;
+
+            let [value_column, lo_column, hi_column, _, _] = config.advice;
+            let value = layouter.assign_region(
+                || "value",
+                |mut region| region.assign_advice(|| "value", value_column, 0, || self.value),
+            )?;
+
+            let constrained = match self.bounds {
+                Bounds::Constant(lo, hi) => {
+                    let constrained = config.assert_in_interval(
+                        layouter.namespace(|| "interval"),
+                        &value,
+                        lo,
+                        hi,
+                    )?;
+                    assert!(matches!(
+                        constrained.interval(),
+                        (Bound::Constant(l), Bound::Constant(h)) if *l == lo && *h == hi
+                    ));
+                    constrained
+                }
+                Bounds::Public(num_bits) => {
+                    let (lo, hi) = layouter.assign_region(
+                        || "public bounds",
+                        |mut region| {
+                            let lo = region.assign_advice_from_instance(
+                                || "lo",
+                                instance,
+                                0,
+                                lo_column,
+                                0,
+                            )?;
+                            let hi = region.assign_advice_from_instance(
+                                || "hi",
+                                instance,
+                                1,
+                                hi_column,
+                                0,
+                            )?;
+                            Ok((lo, hi))
+                        },
+                    )?;
+                    let constrained = config.assert_in_interval_cells(
+                        layouter.namespace(|| "interval"),
+                        &value,
+                        &lo,
+                        &hi,
+                        num_bits,
+                    )?;
+                    assert!(matches!(
+                        constrained.interval(),
+                        (Bound::Cell(_), Bound::Cell(_))
+                    ));
+                    constrained
+                }
+            };
+            constrained
+                .value()
+                .value()
+                .zip(value.value())
+                .assert_if_known(|(a, b)| a == b);
+            Ok(())
+        }
+    }
+
+    fn verifies(value: Fp, lo: Fp, hi: Fp) -> bool {
+        let circuit = MyCircuit {
+            value: Value::known(value),
+            bounds: Bounds::Constant(lo, hi),
+        };
+        MockProver::run(K, &circuit, vec![vec![]])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    fn verifies_public(value: Fp, lo: Fp, hi: Fp, num_bits: usize) -> bool {
+        let circuit = MyCircuit {
+            value: Value::known(value),
+            bounds: Bounds::Public(num_bits),
+        };
+        MockProver::run(K, &circuit, vec![vec![lo, hi]])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn constant_interval() {
+        let (lo, hi) = (Fp::from(10), Fp::from(20));
+        for value in 10..20 {
+            assert!(verifies(Fp::from(value), lo, hi), "{}", value);
+        }
+        for value in [Fp::from(9), Fp::from(20), Fp::zero(), -Fp::from(10)] {
+            assert!(!verifies(value, lo, hi), "{:?}", value);
+        }
+
+        // A single value.
+        assert!(verifies(Fp::from(7), Fp::from(7), Fp::from(8)));
+        assert!(!verifies(Fp::from(8), Fp::from(7), Fp::from(8)));
+    }
+
+    #[test]
+    fn constant_interval_near_modulus() {
+        // p - 10..p - 1
+        let (lo, hi) = (-Fp::from(10), -Fp::one());
+        for value in [-Fp::from(10), -Fp::from(5), -Fp::from(2)] {
+            assert!(verifies(value, lo, hi), "{:?}", value);
+        }
+        for value in [-Fp::from(11), -Fp::one(), Fp::zero(), Fp::from(10)] {
+            assert!(!verifies(value, lo, hi), "{:?}", value);
+        }
+
+        // The interval up to the modulus, p - 10..p, holds p - 1 but not 0.
+        assert!(verifies(-Fp::one(), lo, Fp::zero()));
+        assert!(!verifies(Fp::zero(), lo, Fp::zero()));
+
+        // p - 5..5 wraps around 0.
+        let (lo, hi) = (-Fp::from(5), Fp::from(5));
+        for value in [-Fp::from(5), -Fp::one(), Fp::zero(), Fp::from(4)] {
+            assert!(verifies(value, lo, hi), "{:?}", value);
+        }
+        for value in [-Fp::from(6), Fp::from(5)] {
+            assert!(!verifies(value, lo, hi), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn constant_interval_rejects_unchecked_widths() {
+        // Empty, and wider than half of the field.
+        for (lo, hi) in [
+            (Fp::from(3), Fp::from(3)),
+            (Fp::from(4), Fp::from(3)),
+            (Fp::zero(), -Fp::one()),
+        ] {
+            let circuit = MyCircuit {
+                value: Value::known(lo),
+                bounds: Bounds::Constant(lo, hi),
+            };
+            assert!(MockProver::run(K, &circuit, vec![vec![]]).is_err());
+        }
+    }
+
+    #[test]
+    fn public_interval() {
+        let (lo, hi) = (Fp::from(10), Fp::from(20));
+        for value in 10..20 {
+            assert!(verifies_public(Fp::from(value), lo, hi, 8), "{}", value);
+        }
+        for value in [Fp::from(9), Fp::from(20), -Fp::from(10)] {
+            assert!(!verifies_public(value, lo, hi, 8), "{:?}", value);
+        }
+
+        // Empty and reversed intervals hold nothing.
+        for (lo, hi) in [(Fp::from(15), Fp::from(15)), (Fp::from(20), Fp::from(10))] {
+            for value in [9, 10, 15, 19, 20] {
+                assert!(!verifies_public(Fp::from(value), lo, hi, 8), "{}", value);
+            }
+        }
+
+        // Values too far from both bounds for `num_bits` are refused.
+        assert!(verifies_public(
+            Fp::from(500),
+            Fp::zero(),
+            Fp::from(1000),
+            10
+        ));
+        assert!(!verifies_public(
+            Fp::from(500),
+            Fp::zero(),
+            Fp::from(1000),
+            8
+        ));
+    }
+
+    #[test]
+    fn public_interval_near_modulus() {
+        let (lo, hi) = (-Fp::from(10), Fp::from(10));
+        for value in [-Fp::from(10), -Fp::one(), Fp::zero(), Fp::from(9)] {
+            assert!(verifies_public(value, lo, hi, 8), "{:?}", value);
+        }
+        for value in [-Fp::from(11), Fp::from(10), Fp::from(1 << 20)] {
+            assert!(!verifies_public(value, lo, hi, 8), "{:?}", value);
+        }
+
+        // The widest interval that is checked: 2^252 values below the modulus.
+        let width = Fp::from(2).pow(&[252, 0, 0, 0]);
+        let lo = -width;
+        assert!(verifies_public(-Fp::one(), lo, Fp::zero(), 252));
+        assert!(verifies_public(lo, lo, Fp::zero(), 252));
+        assert!(!verifies_public(lo - Fp::one(), lo, Fp::zero(), 252));
+        assert!(!verifies_public(Fp::zero(), lo, Fp::zero(), 252));
+    }
+
+    #[test]
+    fn mutation_interval() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit {
+            value: Value::known(Fp::from(15)),
+            bounds: Bounds::Public(8),
+        };
+        let public = vec![vec![Fp::from(10), Fp::from(20)]];
+        let report = fuzz(K, &circuit, public, Mutations::Single).unwrap();
+
+        // The value is copied into the interval row, so no single cell can change.
+        assert!(report.is_empty(), "{}", report);
+    }
+}
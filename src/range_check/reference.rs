@@ -0,0 +1,18 @@
+//! Native references and value strategies shared by the range-check property tests.
+
+use halo2_proofs::pasta::Fp;
+use proptest::prelude::*;
+
+/// Values near `0` and `bound`, their negations, and anything else.
+pub(super) fn around(bound: u64) -> impl Strategy<Value = Fp> {
+    prop_oneof![
+        (0..2 * bound).prop_map(Fp::from),
+        (1..bound).prop_map(|v| -Fp::from(v)),
+        any::<u64>().prop_map(Fp::from),
+    ]
+}
+
+/// Native reference: is `value` one of `0..range`?
+pub(super) fn in_range(value: Fp, range: u64) -> bool {
+    (0..range).any(|i| value == Fp::from(i))
+}
@@ -0,0 +1,311 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Region, Value},
+    plonk::{
+        Advice, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector,
+        VirtualCells,
+    },
+    poly::Rotation,
+};
+
+use super::example2::RangeTableConfig;
+
+/// This helper checks that a value fits in `num_bits` bits, for any `num_bits` below
+/// the size of the field, with a table of `K = log2(LOOKUP_RANGE)`-bit windows.
+///
+/// The value is decomposed with a running sum `z_0 = value`,
+/// `z_{i+1} = (z_i - w_i) / 2^K`, where each window `w_i = z_i - 2^K * z_{i+1}` is
+/// looked up in the table and the last `z` must be 0, so that
+/// `value = w_0 + w_1 * 2^K + ...`. When `num_bits` is not a multiple of `K`, the last
+/// window is also looked up shifted left by the `K - r` bits it must not use, which
+/// is the short-range check of its `r` bits.
+///
+///    z     | q_window | q_short |   shift    | q_end
+///  --------+----------+---------+------------+-------
+///   z_0    |    1     |    0    |            |   0
+///   ..     |    1     |    0    |            |   0
+///   z_W-1  |    1     |    1    | 2^(K - r)  |   0
+///   z_W    |    0     |    0    |            |   1
+///
+#[derive(Debug, Clone)]
+pub struct RunningSumConfig<F: FieldExt, const LOOKUP_RANGE: usize> {
+    q_window: Selector,
+    q_short: Selector,
+    q_end: Selector,
+    z: Column<Advice>,
+    shift: Column<Fixed>,
+    pub table: RangeTableConfig<F, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const LOOKUP_RANGE: usize> RunningSumConfig<F, LOOKUP_RANGE> {
+    /// Returns `K`, the number of bits of a window.
+    pub fn window_bits() -> usize {
+        LOOKUP_RANGE.trailing_zeros() as usize
+    }
+
+    /// Configures the running sum against `table`, which may be shared with other
+    /// range checks.
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        z: Column<Advice>,
+        table: RangeTableConfig<F, LOOKUP_RANGE>,
+    ) -> Self {
+        assert!(LOOKUP_RANGE > 1 && LOOKUP_RANGE.is_power_of_two());
+
+        let q_window = meta.complex_selector();
+        let q_short = meta.complex_selector();
+        let q_end = meta.selector();
+        let shift = meta.fixed_column();
+        meta.enable_equality(z);
+
+        // w_i = z_i - 2^K * z_{i+1}
+        let window = move |meta: &mut VirtualCells<'_, F>| {
+            let z_cur = meta.query_advice(z, Rotation::cur());
+            let z_next = meta.query_advice(z, Rotation::next());
+            z_cur - z_next * Expression::Constant(F::from(LOOKUP_RANGE as u64))
+        };
+
+        meta.lookup(|meta| {
+            let q_window = meta.query_selector(q_window);
+            vec![(q_window * window(meta), table.value)]
+        });
+
+        meta.lookup(|meta| {
+            let q_short = meta.query_selector(q_short);
+            let shift = meta.query_fixed(shift, Rotation::cur());
+            vec![(q_short * window(meta) * shift, table.value)]
+        });
+
+        meta.create_gate("running sum", |meta| {
+            let q = meta.query_selector(q_end);
+            let z = meta.query_advice(z, Rotation::cur());
+            Constraints::with_selector(q, [("z ends at 0", z)])
+        });
+
+        Self {
+            q_window,
+            q_short,
+            q_end,
+            z,
+            shift,
+            table,
+        }
+    }
+
+    /// Witnesses `value` and checks that it fits in `num_bits` bits.
+    pub fn witness_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "running sum range check",
+            |mut region| {
+                let z_0 = region.assign_advice(|| "z_0", self.z, 0, || value)?;
+                self.assign_windows(&mut region, z_0, num_bits)
+            },
+        )
+    }
+
+    /// Checks that `value` fits in `num_bits` bits, returning its copy in the running
+    /// sum.
+    pub fn copy_check(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "running sum range check",
+            |mut region| {
+                let z_0 = value.copy_advice(|| "z_0", &mut region, self.z, 0)?;
+                self.assign_windows(&mut region, z_0, num_bits)
+            },
+        )
+    }
+
+    fn assign_windows(
+        &self,
+        region: &mut Region<'_, F>,
+        z_0: AssignedCell<F, F>,
+        num_bits: usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert!(num_bits < F::NUM_BITS as usize);
+
+        let k = Self::window_bits();
+        let num_windows = num_bits.div_ceil(k);
+        let mask = (LOOKUP_RANGE - 1) as u128;
+        let two_pow_k_inv = F::from(LOOKUP_RANGE as u64).invert().unwrap();
+
+        let mut z = z_0.clone();
+        for row in 0..num_windows {
+            self.q_window.enable(region, row)?;
+            let partial_bits = num_bits - row * k;
+            if partial_bits < k {
+                self.q_short.enable(region, row)?;
+                let shift = F::from(1 << (k - partial_bits));
+                region.assign_fixed(|| "shift", self.shift, row, || Value::known(shift))?;
+            }
+
+            let z_next = z.value().map(|z| {
+                let window = F::from_u128(z.get_lower_128() & mask);
+                (*z - window) * two_pow_k_inv
+            });
+            z = region.assign_advice(|| "z", self.z, row + 1, || z_next)?;
+        }
+        self.q_end.enable(region, num_windows)?;
+
+        Ok(z_0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::Circuit,
+    };
+
+    use super::*;
+
+    /// Checks that `value` fits in each of `num_bits`.
+    struct MyCircuit {
+        value: Value<Fp>,
+        num_bits: Vec<usize>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = RunningSumConfig<Fp, 256>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                num_bits: self.num_bits.clone(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let z = meta.advice_column();
+            let table = RangeTableConfig::configure(meta);
+            RunningSumConfig::configure(meta, z, table)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            let (first, rest) = self.num_bits.split_first().unwrap();
+            let value = config.witness_check(layouter.namespace(|| "value"), self.value, *first)?;
+            for num_bits in rest {
+                config.copy_check(layouter.namespace(|| "copy"), &value, *num_bits)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn verifies(value: Fp, num_bits: usize) -> bool {
+        let circuit = MyCircuit {
+            value: Value::known(value),
+            num_bits: vec![num_bits],
+        };
+        MockProver::run(9, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    fn two_pow(bits: usize) -> Fp {
+        Fp::from(2).pow(&[bits as u64, 0, 0, 0])
+    }
+
+    #[test]
+    fn running_sum_range_check() {
+        // Multiples of the window size, and partial last windows of every width.
+        for num_bits in [32, 64, 128, 1, 4, 7, 12, 30, 67, 100, 253] {
+            let bound = two_pow(num_bits);
+            for value in [Fp::zero(), two_pow(num_bits - 1), bound - Fp::one()] {
+                assert!(
+                    verifies(value, num_bits),
+                    "{:?} in {} bits",
+                    value,
+                    num_bits
+                );
+            }
+            for value in [bound, bound + Fp::one(), bound.double(), -Fp::one()] {
+                assert!(
+                    !verifies(value, num_bits),
+                    "{:?} in {} bits",
+                    value,
+                    num_bits
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn running_sum_copy_check() {
+        let circuit = MyCircuit {
+            value: Value::known(Fp::from(u32::MAX as u64)),
+            num_bits: vec![64, 32, 128],
+        };
+        MockProver::run(9, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // The copy refuses the value even though the first check accepts it.
+        let circuit = MyCircuit {
+            value: Value::known(Fp::from(u32::MAX as u64 + 1)),
+            num_bits: vec![64, 32],
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn running_sum_short_window_failure() {
+        // 12 bits are a full window and a 4-bit window, so 2^12 only fails the
+        // short-range check of the last window.
+        let circuit = MyCircuit {
+            value: Value::known(Fp::from(1 << 12)),
+            num_bits: vec![12],
+        };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 1,
+                location: FailureLocation::InRegion {
+                    region: (1, "running sum range check").into(),
+                    offset: 1,
+                },
+            }])
+        );
+    }
+
+    #[test]
+    fn mutation_running_sum() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let circuit = MyCircuit {
+            value: Value::known(Fp::from(0xdead_beef)),
+            num_bits: vec![36],
+        };
+        let report = fuzz(9, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so the value `z_0` can change as long as the first window
+        // stays in the table.
+        assert!(report.free_pairs.is_empty(), "{}", report);
+        assert_eq!(report.free_cells.len(), 1, "{}", report);
+        assert!(report
+            .free_cells
+            .iter()
+            .all(|cell| cell.annotation == "z_0"));
+    }
+}
@@ -0,0 +1,305 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Constraints, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{example3::RangeCheckConfig, interval::bit_length};
+use crate::boolean::AssignedBit;
+
+#[derive(Debug, Clone)]
+/// A value in `-2^(num_bits - 1)..2^(num_bits - 1)`, produced by the SignedConfig.
+pub struct Signed<F: FieldExt> {
+    num_bits: usize,
+    value: AssignedCell<F, F>,
+    sign: AssignedBit<F>,
+    abs: AssignedCell<F, F>,
+}
+
+impl<F: FieldExt> Signed<F> {
+    pub fn num_bits(&self) -> usize {
+        self.num_bits
+    }
+
+    /// The value, with `-x` encoded as `p - x`.
+    pub fn value(&self) -> &AssignedCell<F, F> {
+        &self.value
+    }
+
+    /// 1 if the value is negative.
+    pub fn sign(&self) -> &AssignedBit<F> {
+        &self.sign
+    }
+
+    /// The absolute value, which is up to `2^(num_bits - 1)` for the minimum.
+    pub fn abs(&self) -> &AssignedCell<F, F> {
+        &self.abs
+    }
+}
+
+/// This helper checks that a value is a signed integer of `num_bits` bits, with
+/// negative numbers encoded as their negation in the field:
+///
+///   value | sign |  low  |   abs   |   half   | q_signed
+///  -------+------+-------+---------+----------+----------
+///     v   |  s   |   l   |   |v|   | 2^(n-1)  |    1
+///
+/// The value is split into a sign bit and `num_bits - 1` low bits, like two's
+/// complement: `v = l - s * 2^(n-1)`. `l` is copied from a `RangeCheckConfig` that
+/// checks `l < 2^(n-1)`, so `v` is in `-2^(n-1)..2^(n-1)`, and `|v| = v * (1 - 2s)`.
+/// `half` is a fixed column, so one type of circuit serves every `num_bits` up to
+/// `NUM_BITS + 1`.
+#[derive(Debug, Clone)]
+pub struct SignedConfig<F: FieldExt, const NUM_BITS: usize> {
+    q_signed: Selector,
+    advice: [Column<Advice>; 4],
+    half: Column<Fixed>,
+    pub range_check: RangeCheckConfig<F, NUM_BITS>,
+}
+
+impl<F: FieldExt, const NUM_BITS: usize> SignedConfig<F, NUM_BITS> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4]) -> Self {
+        let q_signed = meta.selector();
+        let half = meta.fixed_column();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+        let [value, sign, low, abs] = advice;
+
+        // The low bits are range-checked in the `low` column.
+        let num_bits = meta.fixed_column();
+        let range_check = RangeCheckConfig::configure(meta, num_bits, low);
+
+        meta.create_gate("signed", |meta| {
+            let q = meta.query_selector(q_signed);
+            let [value, sign, low, abs] =
+                [value, sign, low, abs].map(|column| meta.query_advice(column, Rotation::cur()));
+            let half = meta.query_fixed(half, Rotation::cur());
+            let one = Expression::Constant(F::one());
+
+            Constraints::with_selector(
+                q,
+                [
+                    (
+                        "sign is boolean",
+                        sign.clone() * (one.clone() - sign.clone()),
+                    ),
+                    (
+                        "value = low - sign * half",
+                        value.clone() - (low - sign.clone() * half),
+                    ),
+                    (
+                        "abs = value * (1 - 2 * sign)",
+                        abs - value * (one - sign * Expression::Constant(F::from(2))),
+                    ),
+                ],
+            )
+        });
+
+        Self {
+            q_signed,
+            advice,
+            half,
+            range_check,
+        }
+    }
+
+    /// Witnesses `value` and checks that it is in `-2^(num_bits - 1)..2^(num_bits - 1)`.
+    /// Fails with `Error::Synthesis` if `num_bits` is 0 or above `NUM_BITS + 1`.
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        num_bits: usize,
+    ) -> Result<Signed<F>, Error> {
+        if num_bits == 0 || num_bits > NUM_BITS + 1 {
+            return Err(Error::Synthesis);
+        }
+        let half = F::from(2).pow(&[num_bits as u64 - 1, 0, 0, 0]);
+
+        // Values with more than `num_bits - 1` bits are taken as negative; those that
+        // are not in the range fail the range check of `low`.
+        let sign = value.map(|v| bit_length(&v) >= num_bits);
+        let low = value
+            .zip(sign)
+            .map(|(v, sign)| if sign { v + half } else { v });
+        let low_checked = self.range_check.assign(
+            layouter.namespace(|| "low bits"),
+            num_bits - 1,
+            low.map(|low| low.into()),
+        )?;
+
+        let [value_column, sign_column, low_column, abs_column] = self.advice;
+        layouter.assign_region(
+            || "signed",
+            |mut region| {
+                self.q_signed.enable(&mut region, 0)?;
+                region.assign_fixed(|| "half", self.half, 0, || Value::known(half))?;
+
+                let value = region.assign_advice(|| "value", value_column, 0, || value)?;
+                let sign_value = sign.map(|sign| F::from(sign as u64));
+                let sign = region.assign_advice(|| "sign", sign_column, 0, || sign_value)?;
+                let low = region.assign_advice(|| "low", low_column, 0, || low)?;
+                region.constrain_equal(low.cell(), low_checked.cell().cell())?;
+
+                let abs = value
+                    .value()
+                    .zip(sign_value)
+                    .map(|(v, sign)| *v * (F::one() - sign.double()));
+                let abs = region.assign_advice(|| "abs", abs_column, 0, || abs)?;
+
+                Ok(Signed {
+                    num_bits,
+                    value,
+                    sign: AssignedBit::new(sign),
+                    abs,
+                })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    const K: u32 = 10;
+
+    /// Checks a signed value of `num_bits` bits and exposes its sign and absolute value.
+    struct MyCircuit {
+        value: Value<Fp>,
+        num_bits: usize,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (SignedConfig<Fp, 8>, Column<Instance>);
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                value: Value::unknown(),
+                num_bits: self.num_bits,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (SignedConfig::configure(meta, advice), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.range_check.table.load(&mut layouter)?;
+
+            let signed =
+                config.assign(layouter.namespace(|| "signed"), self.value, self.num_bits)?;
+            assert_eq!(signed.num_bits(), self.num_bits);
+            layouter.constrain_instance(signed.sign().cell(), instance, 0)?;
+            layouter.constrain_instance(signed.abs().cell(), instance, 1)
+        }
+    }
+
+    fn signed(value: i64) -> Fp {
+        if value < 0 {
+            -Fp::from(value.unsigned_abs())
+        } else {
+            Fp::from(value as u64)
+        }
+    }
+
+    fn circuit(value: i64, num_bits: usize) -> MyCircuit {
+        MyCircuit {
+            value: Value::known(signed(value)),
+            num_bits,
+        }
+    }
+
+    /// Runs the circuit with the honest sign and absolute value as public inputs.
+    fn verifies(value: i64, num_bits: usize) -> bool {
+        let public = vec![Fp::from((value < 0) as u64), Fp::from(value.unsigned_abs())];
+        MockProver::run(K, &circuit(value, num_bits), vec![public])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn signed_range_check() {
+        for num_bits in 1..=9 {
+            let half = 1i64 << (num_bits - 1);
+            for value in [-half, -1, 0, half - 1] {
+                if value >= -half && value < half {
+                    assert!(verifies(value, num_bits), "{} in {} bits", value, num_bits);
+                }
+            }
+            for value in [-half - 1, half, 2 * half, -2 * half - 1, 1000, -1000] {
+                assert!(!verifies(value, num_bits), "{} in {} bits", value, num_bits);
+            }
+        }
+
+        // Every value of 4 bits.
+        for value in -8..8 {
+            assert!(verifies(value, 4), "{}", value);
+        }
+    }
+
+    #[test]
+    fn signed_sign_and_abs() {
+        // -1 is negative with absolute value 1, and nothing else.
+        for public in [[0, 1], [1, 0], [1, 2]] {
+            let public = vec![Fp::from(public[0]), Fp::from(public[1])];
+            let prover = MockProver::run(K, &circuit(-1, 8), vec![public]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // The minimum's absolute value does not fit in the range itself.
+        let public = vec![Fp::one(), Fp::from(256)];
+        MockProver::run(K, &circuit(-256, 9), vec![public])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn signed_near_modulus() {
+        // p - 1 is -1, not a large positive value, and p - 2^8 - 1 is just below the
+        // minimum of 9 bits.
+        assert!(verifies(-1, 9));
+        assert!(!verifies(-257, 9));
+
+        let public = vec![Fp::zero(), -Fp::one()];
+        let prover = MockProver::run(K, &circuit(-1, 9), vec![public]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn signed_rejects_num_bits_outside_table() {
+        for num_bits in [0, 10] {
+            let circuit = circuit(0, num_bits);
+            assert!(MockProver::run(K, &circuit, vec![vec![Fp::zero(); 2]]).is_err());
+        }
+    }
+
+    #[test]
+    fn mutation_signed() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let public = vec![Fp::one(), Fp::from(5)];
+        let report = fuzz(K, &circuit(-5, 4), vec![public], Mutations::Single).unwrap();
+
+        // The sign and absolute value are public, which pins the value.
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,420 @@
+use std::fmt;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Assigned, Column, ConstraintSystem, Error, Expression, Fixed, Selector},
+    poly::Rotation,
+};
+
+use super::{
+    dynamic::DynamicRangeCheckConfig, example2::RangeTableConfig, running_sum::RunningSumConfig,
+};
+
+/// How [`AutoRangeCheckConfig::assign_range`] checks a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeCheckStrategy {
+    /// `v * (1 - v) * ... * (range - 1 - v) = 0` on a single row.
+    Expression,
+    /// `v` and `range - 1 - v` are looked up in the table.
+    Lookup,
+    /// `v`, and `range - 1 - v` unless the range is a power of two, are decomposed into
+    /// windows that are looked up in the table.
+    RunningSum,
+}
+
+/// What a range check costs, as planned by [`AutoRangeCheckConfig::plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeCheckCost {
+    pub strategy: RangeCheckStrategy,
+    /// Rows used in the value column.
+    pub rows: usize,
+    /// Lookups enabled on those rows.
+    pub lookups: usize,
+    /// The highest degree of the gates and lookups that are enabled. The circuit itself
+    /// has at least the degree of the running sum's short-window lookup, 6, whichever
+    /// strategy is used.
+    pub degree: usize,
+}
+
+impl fmt::Display for RangeCheckCost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?}: {} rows, {} lookups, degree {}",
+            self.strategy, self.rows, self.lookups, self.degree
+        )
+    }
+}
+
+/// The degree of a lookup whose inputs have degree `input_degree`, against table columns.
+fn lookup_degree(input_degree: usize) -> usize {
+    2 + input_degree + 1
+}
+
+/// This helper checks that a value is in `0..range` for a range chosen at runtime, with
+/// the cheapest method that fits:
+///
+/// - ranges below the degree of the circuit use the expression of `range_check/example1`,
+///   whose degree `range + 1` does not raise it. The degree is taken once the lookups
+///   below are configured, so it is at least 6,
+/// - ranges up to `LOOKUP_RANGE` use the lookups of `DynamicRangeCheckConfig`,
+/// - larger ranges use the running sum of `RunningSumConfig` on the value and, unless
+///   the range is a power of two, on `range - 1 - value`:
+///
+///        value      |  max  | q_diff
+///       ----------------------------
+///          v        |   m   |   1
+///        m - v      |       |   0
+///
+/// All of them share the value column and the table of `0..LOOKUP_RANGE`.
+#[derive(Debug, Clone)]
+pub struct AutoRangeCheckConfig<F: FieldExt, const LOOKUP_RANGE: usize> {
+    max_degree: usize,
+    value: Column<Advice>,
+    /// `q_expression[r - 1]` checks the range `0..r`.
+    q_expression: Vec<Selector>,
+    q_diff: Selector,
+    max: Column<Fixed>,
+    lookup: DynamicRangeCheckConfig<F>,
+    running_sum: RunningSumConfig<F, LOOKUP_RANGE>,
+    pub table: RangeTableConfig<F, LOOKUP_RANGE>,
+}
+
+impl<F: FieldExt, const LOOKUP_RANGE: usize> AutoRangeCheckConfig<F, LOOKUP_RANGE> {
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self {
+        let q_diff = meta.selector();
+        let max = meta.fixed_column();
+        let table = RangeTableConfig::configure(meta);
+        let lookup =
+            DynamicRangeCheckConfig::configure_with_table(meta, value, table.clone().into());
+        let running_sum = RunningSumConfig::configure(meta, value, table.clone());
+
+        let max_degree = meta.degree();
+        let q_expression: Vec<_> = (1..max_degree).map(|_| meta.selector()).collect();
+
+        meta.create_gate("expression range check", |meta| {
+            let value = meta.query_advice(value, Rotation::cur());
+
+            // Given a range R and a value v, returns the expression
+            // (v) * (1 - v) * (2 - v) * ... * (R - 1 - v)
+            let range_check = |range: usize| {
+                (1..range).fold(value.clone(), |expr, i| {
+                    expr * (Expression::Constant(F::from(i as u64)) - value.clone())
+                })
+            };
+
+            q_expression
+                .iter()
+                .enumerate()
+                .map(|(i, q)| meta.query_selector(*q) * range_check(i + 1))
+                .collect::<Vec<_>>()
+        });
+
+        meta.create_gate("range difference", |meta| {
+            let q = meta.query_selector(q_diff);
+            let diff = meta.query_advice(value, Rotation::next());
+            let value = meta.query_advice(value, Rotation::cur());
+            let max = meta.query_fixed(max, Rotation::cur());
+
+            vec![q * (max - value - diff)]
+        });
+
+        Self {
+            max_degree,
+            value,
+            q_expression,
+            q_diff,
+            max,
+            lookup,
+            running_sum,
+            table,
+        }
+    }
+
+    /// The degree of the circuit when it was configured, which the expressions stay
+    /// within.
+    pub fn max_degree(&self) -> usize {
+        self.max_degree
+    }
+
+    /// Returns the method `assign_range` uses for `range`, and what it costs. Fails with
+    /// `Error::Synthesis` for an empty range.
+    pub fn plan(&self, range: u128) -> Result<RangeCheckCost, Error> {
+        if range == 0 {
+            return Err(Error::Synthesis);
+        }
+        if range < self.max_degree as u128 {
+            return Ok(RangeCheckCost {
+                strategy: RangeCheckStrategy::Expression,
+                rows: 1,
+                lookups: 0,
+                degree: range as usize + 1,
+            });
+        }
+        if range <= LOOKUP_RANGE as u128 {
+            return Ok(RangeCheckCost {
+                strategy: RangeCheckStrategy::Lookup,
+                rows: 1,
+                lookups: 2,
+                degree: lookup_degree(2),
+            });
+        }
+
+        let num_bits = Self::num_bits(range);
+        let window_bits = RunningSumConfig::<F, LOOKUP_RANGE>::window_bits();
+        let windows = num_bits.div_ceil(window_bits);
+        let short = num_bits % window_bits != 0;
+        let decompositions = if range.is_power_of_two() { 1 } else { 2 };
+        Ok(RangeCheckCost {
+            strategy: RangeCheckStrategy::RunningSum,
+            rows: decompositions * (windows + 1) + (decompositions - 1) * 2,
+            lookups: decompositions * (windows + short as usize),
+            degree: lookup_degree(if short { 3 } else { 2 }),
+        })
+    }
+
+    /// Checks that `value` is in `0..range`, and returns it with the cost of the check.
+    pub fn assign_range(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: Value<F>,
+        range: u128,
+    ) -> Result<(AssignedCell<F, F>, RangeCheckCost), Error> {
+        let cost = self.plan(range)?;
+        let cell = match cost.strategy {
+            RangeCheckStrategy::Expression => layouter.assign_region(
+                || "Assign value for expression range check",
+                |mut region| {
+                    self.q_expression[range as usize - 1].enable(&mut region, 0)?;
+                    region.assign_advice(|| "value", self.value, 0, || value)
+                },
+            )?,
+            RangeCheckStrategy::Lookup => self
+                .lookup
+                .assign(layouter, value.map(Assigned::from), range as usize)?
+                .cell()
+                .clone()
+                .evaluate(),
+            RangeCheckStrategy::RunningSum => {
+                let num_bits = Self::num_bits(range);
+                let cell = self.running_sum.witness_check(
+                    layouter.namespace(|| "value"),
+                    value,
+                    num_bits,
+                )?;
+                if !range.is_power_of_two() {
+                    let diff = self.assign_difference(
+                        layouter.namespace(|| "range - 1 - value"),
+                        &cell,
+                        range - 1,
+                    )?;
+                    self.running_sum.copy_check(
+                        layouter.namespace(|| "range - 1 - value"),
+                        &diff,
+                        num_bits,
+                    )?;
+                }
+                cell
+            }
+        };
+        Ok((cell, cost))
+    }
+
+    /// The bits of `range - 1`, so that `0..range` fits in them.
+    fn num_bits(range: u128) -> usize {
+        (u128::BITS - (range - 1).leading_zeros()) as usize
+    }
+
+    /// Returns `max - value`.
+    fn assign_difference(
+        &self,
+        mut layouter: impl Layouter<F>,
+        value: &AssignedCell<F, F>,
+        max: u128,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        layouter.assign_region(
+            || "range difference",
+            |mut region| {
+                self.q_diff.enable(&mut region, 0)?;
+                let max = F::from_u128(max);
+                region.assign_fixed(|| "max", self.max, 0, || Value::known(max))?;
+                let value = value.copy_advice(|| "value", &mut region, self.value, 0)?;
+                let diff = value.value().map(|value| max - value);
+                region.assign_advice(|| "max - value", self.value, 1, || diff)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{circuit::floor_planner::V1, dev::MockProver, pasta::Fp, plonk::Circuit};
+
+    use super::*;
+
+    /// Checks that each value is in its range, and that `assign_range` reports the cost
+    /// that `plan` promised.
+    #[derive(Default)]
+    struct MyCircuit {
+        values: Vec<(Value<Fp>, u128)>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = AutoRangeCheckConfig<Fp, 256>;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                values: self
+                    .values
+                    .iter()
+                    .map(|(_, range)| (Value::unknown(), *range))
+                    .collect(),
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            AutoRangeCheckConfig::configure(meta, value)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+
+            for (value, range) in &self.values {
+                let (_, cost) =
+                    config.assign_range(layouter.namespace(|| "value"), *value, *range)?;
+                assert_eq!(cost, config.plan(*range)?);
+            }
+            Ok(())
+        }
+    }
+
+    fn verifies(value: Fp, range: u128) -> bool {
+        let circuit = MyCircuit {
+            values: vec![(Value::known(value), range)],
+        };
+        MockProver::run(9, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    fn plan(range: u128) -> RangeCheckCost {
+        let mut meta = ConstraintSystem::default();
+        let value = meta.advice_column();
+        let config = AutoRangeCheckConfig::<Fp, 256>::configure(&mut meta, value);
+        config.plan(range).unwrap()
+    }
+
+    #[test]
+    fn assign_range_selects_strategy() {
+        use RangeCheckStrategy::*;
+
+        let expected = [
+            (1, Expression),
+            (5, Expression),
+            (6, Lookup),
+            (100, Lookup),
+            (256, Lookup),
+            (257, RunningSum),
+            (1 << 32, RunningSum),
+            (u64::MAX as u128, RunningSum),
+        ];
+        for (range, strategy) in expected {
+            assert_eq!(plan(range).strategy, strategy, "range {}", range);
+        }
+
+        // A single row of degree 6, the degree the lookups give the circuit.
+        assert_eq!(
+            plan(5).to_string(),
+            "Expression: 1 rows, 0 lookups, degree 6"
+        );
+        assert_eq!(plan(100).to_string(), "Lookup: 1 rows, 2 lookups, degree 5");
+        // Four 8-bit windows and the final 0.
+        assert_eq!(
+            plan(1 << 32).to_string(),
+            "RunningSum: 5 rows, 4 lookups, degree 5"
+        );
+        // 1000 - 1 needs 10 bits: two windows, the second a short one, for the value and
+        // for 999 - value, and the two rows that compute the latter.
+        assert_eq!(
+            plan(1000).to_string(),
+            "RunningSum: 8 rows, 6 lookups, degree 6"
+        );
+    }
+
+    #[test]
+    fn expressions_stay_within_circuit_degree() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let value = meta.advice_column();
+        let config = AutoRangeCheckConfig::<Fp, 256>::configure(&mut meta, value);
+
+        // The short-window lookup of the running sum has an input of degree 3.
+        assert_eq!(config.max_degree(), 6);
+        assert_eq!(meta.degree(), 6);
+        assert!(plan(5).degree <= meta.degree());
+    }
+
+    #[test]
+    fn assign_range() {
+        for range in [1, 2, 5, 6, 100, 256, 257, 1000, 1 << 32, u64::MAX as u128] {
+            let max = Fp::from_u128(range - 1);
+            assert!(verifies(Fp::zero(), range), "0 in 0..{}", range);
+            assert!(verifies(max, range), "{} - 1 in 0..{}", range, range);
+            assert!(
+                !verifies(max + Fp::one(), range),
+                "{} in 0..{}",
+                range,
+                range
+            );
+            assert!(!verifies(-Fp::one(), range), "-1 in 0..{}", range);
+        }
+    }
+
+    #[test]
+    fn assign_range_rejects_empty_range() {
+        let circuit = MyCircuit {
+            values: vec![(Value::known(Fp::zero()), 0)],
+        };
+        assert!(MockProver::run(9, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn mutation_assign_range() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        // The largest value of each range, so that it cannot move up.
+        let values = [(3, 4), (99, 100), (999, 1000)];
+        let circuit = MyCircuit {
+            values: values
+                .iter()
+                .map(|(value, range)| (Value::known(Fp::from(*value)), *range))
+                .collect(),
+        };
+        let report = fuzz(9, &circuit, vec![], Mutations::Single).unwrap();
+
+        // Nothing is public, so the values checked by the expression and the lookup can
+        // still move down by one. The one checked by the running sum only moves together
+        // with `999 - value`.
+        let free: Vec<_> = report
+            .free_cells
+            .iter()
+            .map(|cell| cell.region.as_str())
+            .collect();
+        assert_eq!(
+            free,
+            [
+                "value/Assign value for dynamic range check",
+                "value/Assign value for expression range check"
+            ],
+            "{}",
+            report
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Regression vectors for the example circuits.
+//!
+//! Each example pins a fingerprint of its verifying key and a proof generated from
+//! it. Any change to a gate, lookup, column or copy constraint changes the verifying
+//! key, so the fingerprint no longer matches and the golden proof stops verifying.
+//!
+//! When such a change is intended, regenerate the vectors and commit them:
+//!
+//! ```text
+//! UPDATE_GOLDEN=1 cargo test regression
+//! ```
+
+use std::{fs, path::PathBuf};
+
+use halo2_proofs::{
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+fn golden_path(name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{}.{}", name, extension))
+}
+
+fn update_golden() -> bool {
+    std::env::var_os("UPDATE_GOLDEN").is_some()
+}
+
+fn read_golden(name: &str, extension: &str) -> Vec<u8> {
+    let path = golden_path(name, extension);
+    fs::read(&path).unwrap_or_else(|_| {
+        panic!(
+            "missing {}; generate it with `UPDATE_GOLDEN=1 cargo test regression`",
+            path.display()
+        )
+    })
+}
+
+fn write_golden(name: &str, extension: &str, contents: &[u8]) {
+    let path = golden_path(name, extension);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+}
+
+/// Returns a hex-encoded BLAKE2b-256 hash of the pinned verifying key, which covers
+/// the domain, the pinned constraint system and the fixed and permutation commitments.
+pub(crate) fn vk_fingerprint(vk: &VerifyingKey<EqAffine>) -> String {
+    let pinned = format!("{:?}", vk.pinned());
+    blake2b_simd::Params::new()
+        .hash_length(32)
+        .hash(pinned.as_bytes())
+        .to_hex()
+        .to_string()
+}
+
+/// Checks the verifying key of `circuit` against the fingerprint stored for `name`.
+pub(crate) fn assert_vk_fingerprint<C: Circuit<Fp>>(name: &str, k: u32, circuit: &C) {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+    let fingerprint = vk_fingerprint(&vk);
+
+    if update_golden() {
+        write_golden(name, "vk", format!("{}\n", fingerprint).as_bytes());
+        return;
+    }
+
+    let expected = String::from_utf8(read_golden(name, "vk")).unwrap();
+    assert_eq!(
+        fingerprint,
+        expected.trim(),
+        "verifying key of `{}` changed; this breaks every existing proof",
+        name
+    );
+}
+
+/// Checks that the proof stored for `name` still verifies against the verifying key
+/// of `circuit` for the given instance columns.
+pub(crate) fn assert_golden_proof<C: Circuit<Fp>>(
+    name: &str,
+    k: u32,
+    circuit: &C,
+    instance: &[Vec<Fp>],
+) {
+    let params: Params<EqAffine> = Params::new(k);
+    let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+    let instance: Vec<&[Fp]> = instance.iter().map(|column| &column[..]).collect();
+    let instances = &[&instance[..]];
+
+    if update_golden() {
+        let vk = keygen_vk(&params, circuit).expect("keygen_vk should not fail");
+        let pk = keygen_pk(&params, vk, circuit).expect("keygen_pk should not fail");
+        let mut transcript = Blake2bWrite::<_, _, Challenge255<_>>::init(vec![]);
+        create_proof(
+            &params,
+            &pk,
+            std::slice::from_ref(circuit),
+            instances,
+            OsRng,
+            &mut transcript,
+        )
+        .expect("proof generation should not fail");
+        write_golden(name, "proof", &transcript.finalize());
+    }
+
+    let proof = read_golden(name, "proof");
+    let strategy = SingleVerifier::new(&params);
+    let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(&proof[..]);
+    assert!(
+        verify_proof(&params, &vk, strategy, instances, &mut transcript).is_ok(),
+        "golden proof of `{}` no longer verifies",
+        name
+    );
+}
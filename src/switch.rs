@@ -0,0 +1,338 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, Value},
+    plonk::{Advice, Column, ConstraintSystem, Error, Expression, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::piecewise::Term;
+
+/// Computes `cases[tag](inputs)` for a small `tag`, without a chain of `IsZeroChip`s.
+///
+/// The tag is looked up together with one bit per case in a table that maps every
+/// tag to the bits with only its own case set, and the output is the sum of the case
+/// terms gated by their bits. The table has a row of zeros for rows where the switch
+/// is not enabled, and a column holding 1 on all other rows that the selector is
+/// looked up against, so the zeros cannot be used by an enabled row.
+///
+///  tag | bit_0 .. bit_n | inputs | output | q_switch
+/// -----+----------------+--------+--------+----------
+///   t  |  0 .. 1 .. 0   | x_0 .. |  f_t(x) |    1
+///
+///  table: enabled | tag | bit_0 .. bit_n
+///        ---------+-----+----------------
+///            0    |  0  |  0  ..  0
+///            1    |  0  |  1  ..  0
+///            1    |  1  |  0 1 .. 0
+///           ..
+#[derive(Clone, Debug)]
+pub struct SwitchConfig {
+    q_switch: Selector,
+    tag: Column<Advice>,
+    bits: Vec<Column<Advice>>,
+    inputs: Vec<Column<Advice>>,
+    output: Column<Advice>,
+    cases: Vec<Term>,
+    table_enabled: TableColumn,
+    table_tag: TableColumn,
+    table_bits: Vec<TableColumn>,
+}
+
+impl SwitchConfig {
+    pub fn configure<F: FieldExt>(
+        meta: &mut ConstraintSystem<F>,
+        num_inputs: usize,
+        cases: Vec<Term>,
+    ) -> Self {
+        assert!(!cases.is_empty(), "a switch needs a case");
+
+        let q_switch = meta.complex_selector();
+        let tag = meta.advice_column();
+        let bits: Vec<_> = cases.iter().map(|_| meta.advice_column()).collect();
+        let inputs: Vec<_> = (0..num_inputs).map(|_| meta.advice_column()).collect();
+        let output = meta.advice_column();
+        let table_enabled = meta.lookup_table_column();
+        let table_tag = meta.lookup_table_column();
+        let table_bits: Vec<_> = cases.iter().map(|_| meta.lookup_table_column()).collect();
+
+        meta.enable_equality(tag);
+        meta.enable_equality(output);
+        for column in &inputs {
+            meta.enable_equality(*column);
+        }
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_switch);
+            let tag = meta.query_advice(tag, Rotation::cur());
+
+            let mut lookups = vec![(q.clone(), table_enabled), (q.clone() * tag, table_tag)];
+            for (bit, table_bit) in bits.iter().zip(&table_bits) {
+                let bit = meta.query_advice(*bit, Rotation::cur());
+                lookups.push((q.clone() * bit, *table_bit));
+            }
+            lookups
+        });
+
+        meta.create_gate("switch", |meta| {
+            let q = meta.query_selector(q_switch);
+            let inputs: Vec<_> = inputs
+                .iter()
+                .map(|column| meta.query_advice(*column, Rotation::cur()))
+                .collect();
+            let output = meta.query_advice(output, Rotation::cur());
+
+            let selected = bits.iter().zip(&cases).fold(
+                Expression::Constant(F::zero()),
+                |acc, (bit, case)| {
+                    let bit = meta.query_advice(*bit, Rotation::cur());
+                    acc + bit * case.expression(&inputs)
+                },
+            );
+            vec![q * (output - selected)]
+        });
+
+        Self {
+            q_switch,
+            tag,
+            bits,
+            inputs,
+            output,
+            cases,
+            table_enabled,
+            table_tag,
+            table_bits,
+        }
+    }
+
+    pub fn load<F: FieldExt>(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        layouter.assign_table(
+            || "load switch table",
+            |mut table| {
+                // Row 0 is what disabled rows look up.
+                let rows = (0..=self.cases.len()).map(|row| (row.checked_sub(1), row));
+                for (case, offset) in rows {
+                    let enabled = F::from(case.is_some() as u64);
+                    let tag = F::from(case.unwrap_or(0) as u64);
+                    table.assign_cell(
+                        || "enabled",
+                        self.table_enabled,
+                        offset,
+                        || Value::known(enabled),
+                    )?;
+                    table.assign_cell(|| "tag", self.table_tag, offset, || Value::known(tag))?;
+                    for (index, column) in self.table_bits.iter().enumerate() {
+                        let bit = F::from((case == Some(index)) as u64);
+                        table.assign_cell(|| "bit", *column, offset, || Value::known(bit))?;
+                    }
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Returns the cell holding `cases[tag](inputs)`. Tags without a case fail the
+    /// lookup.
+    pub fn assign<F: FieldExt>(
+        &self,
+        layouter: impl Layouter<F>,
+        tag: &AssignedCell<F, F>,
+        inputs: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let case = tag.value().map(|tag| {
+            let tag = tag.get_lower_128();
+            (0..self.cases.len()).find(|case| *case as u128 == tag)
+        });
+        self.assign_case(layouter, tag, inputs, case)
+    }
+
+    /// Lays out the switch with the bit of `case` set, whatever the tag is.
+    fn assign_case<F: FieldExt>(
+        &self,
+        mut layouter: impl Layouter<F>,
+        tag: &AssignedCell<F, F>,
+        inputs: &[AssignedCell<F, F>],
+        case: Value<Option<usize>>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        assert_eq!(inputs.len(), self.inputs.len());
+
+        layouter.assign_region(
+            || "switch",
+            |mut region| {
+                self.q_switch.enable(&mut region, 0)?;
+                tag.copy_advice(|| "tag", &mut region, self.tag, 0)?;
+                for (input, column) in inputs.iter().zip(&self.inputs) {
+                    input.copy_advice(|| "input", &mut region, *column, 0)?;
+                }
+                for (index, column) in self.bits.iter().enumerate() {
+                    let bit = case.map(|case| F::from((case == Some(index)) as u64));
+                    region.assign_advice(|| "bit", *column, 0, || bit)?;
+                }
+
+                let values: Value<Vec<F>> =
+                    inputs.iter().map(|input| input.value().copied()).collect();
+                let output = case.zip(values).map(|(case, values)| {
+                    case.map_or(F::zero(), |case| self.cases[case].evaluate(&values))
+                });
+                region.assign_advice(|| "output", self.output, 0, || output)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    /// A small ALU on `x` and `y`, indexed by opcode.
+    fn cases() -> Vec<Term> {
+        let [x, y] = [0, 1].map(Term::input);
+        vec![
+            x.clone() + y.clone(),
+            x.clone() - y.clone(),
+            x.clone() * y.clone(),
+            x.clone(),
+            y.clone(),
+            Term::constant(0),
+            x.clone() * x.clone(),
+            x.clone() + y.clone() + Term::constant(1),
+            Term::constant(2) * x.clone(),
+            y.clone() * y - x,
+        ]
+    }
+
+    /// Reads `tag`, `x` and `y` from instance rows 0 to 2 and exposes the output of
+    /// the switch at row 3.
+    #[derive(Default)]
+    struct MyCircuit {
+        /// Sets the bits of this case instead of the one the tag selects.
+        forged_case: Option<Option<usize>>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (SwitchConfig, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                forged_case: self.forged_case,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            (SwitchConfig::configure(meta, 2, cases()), instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.load(&mut layouter)?;
+
+            let (tag, inputs) = layouter.assign_region(
+                || "load",
+                |mut region| {
+                    let tag =
+                        region.assign_advice_from_instance(|| "tag", instance, 0, config.tag, 0)?;
+                    let inputs = (0..2)
+                        .map(|i| {
+                            let column = config.inputs[i];
+                            region.assign_advice_from_instance(
+                                || "input",
+                                instance,
+                                i + 1,
+                                column,
+                                0,
+                            )
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Ok((tag, inputs))
+                },
+            )?;
+
+            let output = match self.forged_case {
+                None => config.assign(layouter.namespace(|| "switch"), &tag, &inputs)?,
+                Some(case) => config.assign_case(
+                    layouter.namespace(|| "switch"),
+                    &tag,
+                    &inputs,
+                    Value::known(case),
+                )?,
+            };
+            layouter.constrain_instance(output.cell(), instance, 3)
+        }
+    }
+
+    fn verifies(circuit: &MyCircuit, tag: Fp, x: u64, y: u64, output: Fp) -> bool {
+        let instance = vec![tag, Fp::from(x), Fp::from(y), output];
+        MockProver::run(5, circuit, vec![instance])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn switch_selects_case() {
+        let circuit = MyCircuit::default();
+        for (x, y) in [(3, 5), (9, 2), (0, 0)] {
+            let inputs = [Fp::from(x), Fp::from(y)];
+            for (tag, case) in cases().iter().enumerate() {
+                let tag = Fp::from(tag as u64);
+                let output = case.evaluate(&inputs);
+                assert!(verifies(&circuit, tag, x, y, output));
+                assert!(!verifies(&circuit, tag, x, y, output + Fp::one()));
+            }
+        }
+    }
+
+    #[test]
+    fn switch_rejects_unknown_tags() {
+        let circuit = MyCircuit::default();
+        for tag in [Fp::from(10), Fp::from(16), -Fp::one()] {
+            assert!(!verifies(&circuit, tag, 3, 5, Fp::zero()));
+        }
+    }
+
+    #[test]
+    fn switch_rejects_forged_case_bits() {
+        let tag = Fp::from(3);
+        let inputs = [Fp::from(3), Fp::from(5)];
+
+        // The bits of another case, so that the output of case 2 verifies for tag 3.
+        let circuit = MyCircuit {
+            forged_case: Some(Some(2)),
+        };
+        assert!(!verifies(&circuit, tag, 3, 5, cases()[2].evaluate(&inputs)));
+
+        // No bits at all, which pins the output to 0.
+        let circuit = MyCircuit {
+            forged_case: Some(None),
+        };
+        assert!(!verifies(&circuit, tag, 3, 5, Fp::zero()));
+
+        // Forged bits only pass with a tag that has them.
+        let circuit = MyCircuit {
+            forged_case: Some(Some(2)),
+        };
+        let tag = Fp::from(2);
+        assert!(verifies(&circuit, tag, 3, 5, cases()[2].evaluate(&inputs)));
+    }
+
+    #[test]
+    fn mutation_switch() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let instance = vec![Fp::from(7), Fp::from(3), Fp::from(5), Fp::from(9)];
+        let report = fuzz(5, &MyCircuit::default(), vec![instance], Mutations::Pairs).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}
@@ -0,0 +1,270 @@
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{Layouter, Value},
+    plonk::{ConstraintSystem, Error, TableColumn},
+};
+
+/// The contents of a lookup table that chips can share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableSpec {
+    /// The values `0..range`.
+    Range { range: usize },
+    /// `(a, b, a ^ b)` for all `a` and `b` of `bits` bits.
+    Xor { bits: u32 },
+}
+
+impl TableSpec {
+    /// The name a table is registered under. Two specs with the same name have the same
+    /// contents.
+    pub fn name(&self) -> String {
+        match self {
+            TableSpec::Range { range } => format!("range 0..{}", range),
+            TableSpec::Xor { bits } => format!("xor of {} bits", bits),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            TableSpec::Range { .. } => 1,
+            TableSpec::Xor { .. } => 3,
+        }
+    }
+
+    pub fn rows(&self) -> Vec<Vec<u64>> {
+        match *self {
+            TableSpec::Range { range } => (0..range as u64).map(|value| vec![value]).collect(),
+            TableSpec::Xor { bits } => {
+                let values = 0..1u64 << bits;
+                values
+                    .clone()
+                    .flat_map(|a| values.clone().map(move |b| vec![a, b, a ^ b]))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Hands out the columns of lookup tables to the chips of a circuit, so that chips
+/// asking for the same table share its columns and it is loaded once.
+///
+/// Chips request tables while they are configured, e.g. with
+/// `RangeCheckConfig::configure_with_registry` or `XorConfig::configure`. The circuit
+/// keeps the registry in its config and loads every table at the start of
+/// `synthesize`, in place of loading each chip's table.
+#[derive(Debug, Clone)]
+pub struct TableRegistry<F: FieldExt> {
+    tables: Vec<(TableSpec, Vec<TableColumn>)>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> Default for TableRegistry<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: FieldExt> TableRegistry<F> {
+    pub fn new() -> Self {
+        Self {
+            tables: vec![],
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the columns of the table, allocating them the first time it is
+    /// requested.
+    pub fn request(&mut self, meta: &mut ConstraintSystem<F>, spec: TableSpec) -> Vec<TableColumn> {
+        if let Some(columns) = self.get(&spec.name()) {
+            return columns.to_vec();
+        }
+        let columns: Vec<_> = (0..spec.width())
+            .map(|_| meta.lookup_table_column())
+            .collect();
+        self.tables.push((spec, columns.clone()));
+        columns
+    }
+
+    /// Returns the columns of the table registered under `name`.
+    pub fn get(&self, name: &str) -> Option<&[TableColumn]> {
+        self.tables
+            .iter()
+            .find(|(spec, _)| spec.name() == name)
+            .map(|(_, columns)| columns.as_slice())
+    }
+
+    /// The registered tables, in the order they were first requested.
+    pub fn specs(&self) -> impl Iterator<Item = &TableSpec> {
+        self.tables.iter().map(|(spec, _)| spec)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// Loads every registered table.
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        for (spec, columns) in &self.tables {
+            layouter.assign_table(
+                || format!("load {} table", spec.name()),
+                |mut table| {
+                    for (offset, row) in spec.rows().into_iter().enumerate() {
+                        for (column, value) in columns.iter().zip(row) {
+                            table.assign_cell(
+                                || "value",
+                                *column,
+                                offset,
+                                || Value::known(F::from(value)),
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::MockProver,
+        pasta::Fp,
+        plonk::{Advice, Circuit, Column},
+    };
+
+    use super::*;
+    use crate::{
+        range_check::{
+            example2::{RangeCheckConfig, RangeTableConfig},
+            running_sum::RunningSumConfig,
+        },
+        xor::XorConfig,
+    };
+
+    /// Two range checks and two XOR chips, which share two tables.
+    #[derive(Default)]
+    struct MyCircuit;
+
+    #[derive(Debug, Clone)]
+    struct MyConfig {
+        tables: TableRegistry<Fp>,
+        range_check: RangeCheckConfig<Fp, 8, 256>,
+        running_sum: RunningSumConfig<Fp, 256>,
+        xor: XorConfig<Fp, 4>,
+        other_xor: XorConfig<Fp, 4>,
+        value: Column<Advice>,
+    }
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let mut tables = TableRegistry::new();
+
+            let range_check =
+                RangeCheckConfig::configure_with_registry(meta, advice[0], &mut tables);
+            let table = RangeTableConfig::configure_with_registry(meta, &mut tables);
+            let running_sum = RunningSumConfig::configure(meta, advice[1], table);
+            let xor = XorConfig::configure(meta, advice, &mut tables);
+            let other_xor = XorConfig::configure(meta, advice, &mut tables);
+
+            MyConfig {
+                tables,
+                range_check,
+                running_sum,
+                xor,
+                other_xor,
+                value: advice[0],
+            }
+        }
+
+        fn synthesize(
+            &self,
+            config: MyConfig,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            config.tables.load(&mut layouter)?;
+
+            config.range_check.assign_lookup(
+                layouter.namespace(|| "byte"),
+                Value::known(Fp::from(200).into()),
+            )?;
+            config.running_sum.witness_check(
+                layouter.namespace(|| "u16"),
+                Value::known(Fp::from(0xbeef)),
+                16,
+            )?;
+
+            let [a, b] = [9, 12].map(|v| {
+                layouter.assign_region(
+                    || "input",
+                    |mut region| {
+                        region.assign_advice(
+                            || "input",
+                            config.value,
+                            0,
+                            || Value::known(Fp::from(v)),
+                        )
+                    },
+                )
+            });
+            let out = config.xor.xor(layouter.namespace(|| "xor"), &a?, &b?)?;
+            config
+                .other_xor
+                .xor(layouter.namespace(|| "xor again"), &out, &out)?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registry_shares_tables() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let config = MyCircuit::configure(&mut meta);
+
+        let names: Vec<_> = config.tables.specs().map(TableSpec::name).collect();
+        assert_eq!(names, ["range 0..256", "xor of 4 bits"]);
+        assert_eq!(
+            config.range_check.table.value,
+            config.running_sum.table.value
+        );
+        assert_eq!(config.xor.table(), config.other_xor.table());
+        assert_eq!(
+            config.tables.get("xor of 4 bits"),
+            Some(&config.xor.table()[..])
+        );
+        assert_eq!(config.tables.get("range 0..8"), None);
+    }
+
+    #[test]
+    fn registry_loads_each_table_once() {
+        MockProver::run(9, &MyCircuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let layout = crate::analysis::layout::measure(&MyCircuit).unwrap();
+        // Two tables, the two range checks, two inputs and two XORs.
+        assert_eq!(layout.regions, 8, "{}", layout);
+    }
+
+    #[test]
+    fn table_rows() {
+        assert_eq!(TableSpec::Range { range: 3 }.rows(), [[0], [1], [2]]);
+        let xor = TableSpec::Xor { bits: 2 }.rows();
+        assert_eq!(xor.len(), 16);
+        assert!(xor.iter().all(|row| row[0] ^ row[1] == row[2]));
+    }
+}
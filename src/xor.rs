@@ -0,0 +1,190 @@
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter},
+    plonk::{Advice, Column, ConstraintSystem, Error, Selector, TableColumn},
+    poly::Rotation,
+};
+
+use crate::tables::{TableRegistry, TableSpec};
+
+/// This helper computes `a ^ b` for values of `BITS` bits with a lookup in a table of
+/// all `(a, b, a ^ b)`, shared through a `TableRegistry`:
+///
+///   a | b | out | q_xor | table_a | table_b | table_out
+///  ---+---+-----+-------+---------+---------+-----------
+///   a | b | a^b |   1   |    0    |    0    |     0
+///     |   |     |       |    0    |    1    |     1
+///
+/// Inputs of more than `BITS` bits are not in the table, so they fail the lookup.
+#[derive(Debug, Clone)]
+pub struct XorConfig<F: FieldExt, const BITS: u32> {
+    q_xor: Selector,
+    advice: [Column<Advice>; 3],
+    table: [TableColumn; 3],
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt, const BITS: u32> XorConfig<F, BITS> {
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        advice: [Column<Advice>; 3],
+        tables: &mut TableRegistry<F>,
+    ) -> Self {
+        let q_xor = meta.complex_selector();
+        let table: [TableColumn; 3] = tables
+            .request(meta, TableSpec::Xor { bits: BITS })
+            .try_into()
+            .unwrap();
+        for column in advice {
+            meta.enable_equality(column);
+        }
+
+        meta.lookup(|meta| {
+            let q = meta.query_selector(q_xor);
+            advice
+                .iter()
+                .zip(table)
+                .map(|(column, table)| {
+                    (
+                        q.clone() * meta.query_advice(*column, Rotation::cur()),
+                        table,
+                    )
+                })
+                .collect()
+        });
+
+        Self {
+            q_xor,
+            advice,
+            table,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// The columns of the table, which are shared with other chips of the registry.
+    pub fn table(&self) -> [TableColumn; 3] {
+        self.table
+    }
+
+    /// Returns `a ^ b`.
+    pub fn xor(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let [a_column, b_column, out_column] = self.advice;
+
+        layouter.assign_region(
+            || "xor",
+            |mut region| {
+                self.q_xor.enable(&mut region, 0)?;
+                a.copy_advice(|| "a", &mut region, a_column, 0)?;
+                b.copy_advice(|| "b", &mut region, b_column, 0)?;
+
+                let out = a
+                    .value()
+                    .zip(b.value())
+                    .map(|(a, b)| F::from_u128(a.get_lower_128() ^ b.get_lower_128()));
+                region.assign_advice(|| "a ^ b", out_column, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use halo2_proofs::{
+        circuit::floor_planner::V1,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::{Circuit, Instance},
+    };
+
+    use super::*;
+
+    /// Returns `a ^ b` for the public `a` and `b`.
+    #[derive(Default)]
+    struct MyCircuit;
+
+    impl Circuit<Fp> for MyCircuit {
+        type Config = (XorConfig<Fp, 4>, TableRegistry<Fp>, Column<Instance>);
+        type FloorPlanner = V1;
+
+        fn without_witnesses(&self) -> Self {
+            Self
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+            let mut tables = TableRegistry::new();
+            let xor = XorConfig::configure(meta, advice, &mut tables);
+            (xor, tables, instance)
+        }
+
+        fn synthesize(
+            &self,
+            (config, tables, instance): Self::Config,
+            mut layouter: impl Layouter<Fp>,
+        ) -> Result<(), Error> {
+            tables.load(&mut layouter)?;
+
+            let [a_column, b_column, _] = config.advice;
+            let (a, b) = layouter.assign_region(
+                || "inputs",
+                |mut region| {
+                    let a = region.assign_advice_from_instance(|| "a", instance, 0, a_column, 0)?;
+                    let b = region.assign_advice_from_instance(|| "b", instance, 1, b_column, 0)?;
+                    Ok((a, b))
+                },
+            )?;
+            let out = config.xor(layouter.namespace(|| "xor"), &a, &b)?;
+            layouter.constrain_instance(out.cell(), instance, 2)
+        }
+    }
+
+    fn verifies(a: u64, b: u64, out: u64) -> bool {
+        let public = vec![Fp::from(a), Fp::from(b), Fp::from(out)];
+        MockProver::run(9, &MyCircuit, vec![public])
+            .unwrap()
+            .verify()
+            .is_ok()
+    }
+
+    #[test]
+    fn xor() {
+        for a in 0..16 {
+            for b in 0..16 {
+                assert!(verifies(a, b, a ^ b), "{} ^ {}", a, b);
+            }
+        }
+        assert!(!verifies(3, 5, 7));
+    }
+
+    #[test]
+    fn xor_refuses_wide_inputs() {
+        let public = vec![Fp::from(16), Fp::from(1), Fp::from(17)];
+        let prover = MockProver::run(9, &MyCircuit, vec![public]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion {
+                    region: (2, "xor").into(),
+                    offset: 0
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn mutation_xor() {
+        use crate::analysis::mutation::{fuzz, Mutations};
+
+        let public = vec![Fp::from(9), Fp::from(12), Fp::from(5)];
+        let report = fuzz(9, &MyCircuit, vec![public], Mutations::Single).unwrap();
+        assert!(report.is_empty(), "{}", report);
+    }
+}